@@ -11,6 +11,10 @@ struct Opts {
     listener: PathList,
     derived_from: PathList,
     derived_from_mut: PathList,
+    derived_from_many: PathList,
+    hydrate: bool,
+    on_active: Option<syn::Path>,
+    on_inactive: Option<syn::Path>,
 }
 
 pub(crate) fn derive(input: DeriveInput) -> TokenStream {
@@ -50,6 +54,41 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
         })
         .collect();
 
+    let derived_from_many_init = if opts.derived_from_many.is_empty() {
+        quote!()
+    } else {
+        let sources = opts.derived_from_many.iter();
+        quote! {
+            cx.derived_from_many::<(#(#sources,)*), Self>();
+        }
+    };
+
+    let hydrate = if opts.hydrate {
+        quote! {
+            if let Some(state) = ::yewdux::hydrate::init::<Self>(cx) {
+                return state;
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let on_active = opts.on_active.map(|path| {
+        quote! {
+            fn on_active(&self, cx: &::yewdux::Context) {
+                #path(self, cx)
+            }
+        }
+    });
+
+    let on_inactive = opts.on_inactive.map(|path| {
+        quote! {
+            fn on_inactive(&self, cx: &::yewdux::Context) {
+                #path(self, cx)
+            }
+        }
+    });
+
     let impl_ = match opts.storage {
         Some(storage) => {
             let area = match storage.as_ref() {
@@ -81,8 +120,10 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
                     #(#extra_listeners)*
                     #(#derived_from_init)*
                     #(#derived_from_mut_init)*
+                    #derived_from_many_init
 
                     #sync
+                    #hydrate
 
                     match ::yewdux::storage::load(#area) {
                         Ok(val) => val.unwrap_or_default(),
@@ -100,6 +141,8 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
                     #(#extra_listeners)*
                     #(#derived_from_init)*
                     #(#derived_from_mut_init)*
+                    #derived_from_many_init
+                    #hydrate
                     Default::default()
                 }
             }
@@ -109,6 +152,8 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
                 #(#extra_listeners)*
                 #(#derived_from_init)*
                 #(#derived_from_mut_init)*
+                #derived_from_many_init
+                #hydrate
                 Default::default()
             }
         },
@@ -122,6 +167,9 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
             fn should_notify(&self, other: &Self) -> bool {
                 self != other
             }
+
+            #on_active
+            #on_inactive
         }
     }
 }
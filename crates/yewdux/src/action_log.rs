@@ -0,0 +1,345 @@
+//! Recording reducers as serializable actions, and replaying them.
+//!
+//! Reducers applied with [Dispatch::apply_recorded](crate::Dispatch::apply_recorded) are saved to
+//! a bounded [ActionLog] on the [Context]. The log may be serialized (for example, attached to a bug
+//! report), and later replayed against a fresh context with a [Replayer] to reproduce the same state.
+//!
+//! Actions are identified by the type names of their store and reducer, so a log should only be
+//! replayed by the same build of an application that recorded it.
+//!
+//! ```
+//! use std::rc::Rc;
+//!
+//! use serde::{Deserialize, Serialize};
+//! use yewdux::{action_log::Replayer, prelude::*};
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Counter(u32);
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Add(u32);
+//! impl Reducer<Counter> for Add {
+//!     fn apply(self, counter: Rc<Counter>) -> Rc<Counter> {
+//!         Counter(counter.0 + self.0).into()
+//!     }
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! let dispatch = Dispatch::<Counter>::new(&cx);
+//! dispatch.apply_recorded(Add(1));
+//! dispatch.apply_recorded(Add(2));
+//!
+//! let report = serde_json::to_string(&cx.action_log().actions()).unwrap();
+//!
+//! // Later...
+//! let actions: Vec<_> = serde_json::from_str(&report).unwrap();
+//! let replayed = yewdux::Context::new();
+//! Replayer::new()
+//!     .register::<Counter, Add>()
+//!     .replay(&replayed, &actions)
+//!     .unwrap();
+//!
+//! assert_eq!(replayed.get::<Counter>().0, 3);
+//! ```
+use std::{any::type_name, collections::HashMap, collections::VecDeque};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    mrc::Mrc,
+    store::{Reducer, Store},
+    Context,
+};
+
+/// Number of actions kept in a log unless set otherwise.
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// A recorded reducer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    /// Type name of the store the reducer was applied to.
+    pub store: String,
+    /// Type name of the reducer.
+    pub reducer: String,
+    /// The serialized reducer.
+    pub payload: serde_json::Value,
+}
+
+impl Action {
+    fn new<S, R: Serialize>(reducer: &R) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            store: type_name::<S>().to_string(),
+            reducer: type_name::<R>().to_string(),
+            payload: serde_json::to_value(reducer)?,
+        })
+    }
+}
+
+pub(crate) struct Log {
+    actions: VecDeque<Action>,
+    capacity: usize,
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self {
+            actions: Default::default(),
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+}
+
+impl Log {
+    fn truncate(&mut self) {
+        while self.actions.len() > self.capacity {
+            self.actions.pop_front();
+        }
+    }
+}
+
+/// Handle to the actions recorded in a [Context]. When full, the oldest actions are discarded.
+#[derive(Clone)]
+pub struct ActionLog(pub(crate) Mrc<Log>);
+
+impl std::fmt::Debug for ActionLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionLog")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl ActionLog {
+    /// All recorded actions, oldest first.
+    pub fn actions(&self) -> Vec<Action> {
+        self.0.borrow().actions.iter().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.borrow().actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().actions.is_empty()
+    }
+
+    /// Maximum number of actions kept.
+    pub fn capacity(&self) -> usize {
+        self.0.borrow().capacity
+    }
+
+    /// Set maximum number of actions kept, discarding the oldest actions if there are too many.
+    pub fn set_capacity(&self, capacity: usize) {
+        self.0.with_mut(|log| {
+            log.capacity = capacity;
+            log.truncate();
+        });
+    }
+
+    /// Discard all recorded actions.
+    pub fn clear(&self) {
+        self.0.with_mut(|log| log.actions.clear());
+    }
+
+    /// Serialize `reducer` to be recorded with [Self::record], logging an error if it can't be.
+    pub(crate) fn prepare<S, R: Serialize>(reducer: &R) -> Option<Action> {
+        Action::new::<S, R>(reducer)
+            .map_err(|err| crate::log::error!("Unable to record {}: {:?}", type_name::<R>(), err))
+            .ok()
+    }
+
+    pub(crate) fn record(&self, action: Action) {
+        self.0.with_mut(|log| {
+            log.actions.push_back(action);
+            log.truncate();
+        });
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error("No reducer {reducer} registered for store {store}")]
+    Unregistered { store: String, reducer: String },
+    #[error("A serde error occurred")]
+    Serde(#[from] serde_json::Error),
+}
+
+type Apply = Box<dyn Fn(&Context, serde_json::Value) -> Result<(), serde_json::Error>>;
+
+/// Applies recorded [Action]s to a context. Every reducer that may appear in a log must be
+/// registered first.
+#[derive(Default)]
+pub struct Replayer {
+    reducers: HashMap<(&'static str, &'static str), Apply>,
+}
+
+impl Replayer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Allow replaying reducer `R` for store `S`.
+    pub fn register<S, R>(mut self) -> Self
+    where
+        S: Store,
+        R: Reducer<S> + Serialize + DeserializeOwned + 'static,
+    {
+        self.reducers.insert(
+            (type_name::<S>(), type_name::<R>()),
+            Box::new(|cx, payload| {
+                let reducer = serde_json::from_value::<R>(payload)?;
+                cx.reduce_recorded::<S, R>(reducer);
+                Ok(())
+            }),
+        );
+
+        self
+    }
+
+    /// Apply all actions in order. Replayed actions are recorded in the log of `cx`, so it may be
+    /// compared with the original. Stops at the first action that can't be applied.
+    pub fn replay(&self, cx: &Context, actions: &[Action]) -> Result<(), ReplayError> {
+        for action in actions {
+            let apply = self
+                .reducers
+                .get(&(action.store.as_str(), action.reducer.as_str()))
+                .ok_or_else(|| ReplayError::Unregistered {
+                    store: action.store.clone(),
+                    reducer: action.reducer.clone(),
+                })?;
+
+            apply(cx, action.payload.clone())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::Dispatch;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum Msg {
+        Add(u32),
+        Double,
+    }
+
+    impl Reducer<TestState> for Msg {
+        fn apply(self, state: Rc<TestState>) -> Rc<TestState> {
+            match self {
+                Msg::Add(value) => TestState(state.0 + value).into(),
+                Msg::Double => TestState(state.0 * 2).into(),
+            }
+        }
+    }
+
+    #[test]
+    fn apply_recorded_records_action() {
+        let cx = Context::new();
+        Dispatch::<TestState>::new(&cx).apply_recorded(Msg::Add(1));
+
+        assert_eq!(
+            cx.action_log().actions(),
+            vec![Action {
+                store: type_name::<TestState>().to_string(),
+                reducer: type_name::<Msg>().to_string(),
+                payload: serde_json::json!({ "Add": 1 }),
+            }]
+        );
+        assert_eq!(cx.get::<TestState>().0, 1);
+    }
+
+    #[test]
+    fn vetoed_action_is_not_recorded() {
+        struct Veto;
+        impl crate::middleware::Middleware for Veto {
+            fn on_reduce(&self, _cx: &Context, change: &mut crate::middleware::Change) {
+                change.veto();
+            }
+        }
+
+        let cx = Context::new();
+        cx.add_middleware(Veto);
+        Dispatch::<TestState>::new(&cx).apply_recorded(Msg::Add(1));
+
+        assert!(cx.action_log().is_empty());
+        assert_eq!(cx.get::<TestState>().0, 0);
+    }
+
+    #[test]
+    fn apply_does_not_record() {
+        let cx = Context::new();
+        Dispatch::<TestState>::new(&cx).apply(Msg::Add(1));
+        Dispatch::<TestState>::new(&cx).reduce_mut(|state| state.0 += 1);
+
+        assert!(cx.action_log().is_empty());
+    }
+
+    #[test]
+    fn log_is_bounded() {
+        let cx = Context::new();
+        let log = cx.action_log();
+        log.set_capacity(2);
+
+        let dispatch = Dispatch::<TestState>::new(&cx);
+        dispatch.apply_recorded(Msg::Add(1));
+        dispatch.apply_recorded(Msg::Add(2));
+        dispatch.apply_recorded(Msg::Double);
+
+        let actions = log.actions();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].payload, serde_json::json!({ "Add": 2 }));
+        assert_eq!(actions[1].payload, serde_json::json!("Double"));
+
+        log.set_capacity(1);
+        assert_eq!(log.len(), 1);
+    }
+
+    #[test]
+    fn replay_reproduces_state() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<TestState>::new(&cx);
+        dispatch.apply_recorded(Msg::Add(1));
+        dispatch.apply_recorded(Msg::Double);
+        dispatch.apply_recorded(Msg::Add(3));
+
+        let json = serde_json::to_string(&cx.action_log().actions()).unwrap();
+        let actions: Vec<Action> = serde_json::from_str(&json).unwrap();
+
+        let replayed = Context::new();
+        Replayer::new()
+            .register::<TestState, Msg>()
+            .replay(&replayed, &actions)
+            .unwrap();
+
+        assert_eq!(replayed.get::<TestState>(), cx.get::<TestState>());
+        assert_eq!(replayed.action_log().actions(), actions);
+    }
+
+    #[test]
+    fn replay_fails_for_unregistered_reducer() {
+        let cx = Context::new();
+        Dispatch::<TestState>::new(&cx).apply_recorded(Msg::Add(1));
+
+        let result = Replayer::new().replay(&Context::new(), &cx.action_log().actions());
+
+        assert!(matches!(result, Err(ReplayError::Unregistered { .. })));
+    }
+}
@@ -15,6 +15,16 @@ impl AnyMap {
             _marker: std::marker::PhantomData,
         }
     }
+
+    pub(crate) fn insert<T: 'static>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Box::new(value));
+    }
+
+    pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
 }
 
 pub(crate) struct Entry<'a, T: 'static> {
@@ -0,0 +1,376 @@
+//! Stores computed lazily from other stores.
+//!
+//! A [Computed] store is only computed when its value is read, and cached until one of the stores
+//! it read changes. Dependencies are tracked automatically, by reading them through a [Tracker].
+//! So expensive derivations only run when something actually needs them, unlike
+//! [derived stores](crate::derived_from), which are recomputed on every change.
+//!
+//! `Computed<C>` is used like any other store, for example with
+//! `use_store_value::<Computed<C>>()`, and dereferences to `C`. The value is computed on [Deref],
+//! not by [Dispatch::get](crate::Dispatch::get). Subscribers are only notified when it changes.
+//!
+//! ```
+//! use yewdux::{
+//!     computed::{Compute, Computed, Tracker},
+//!     prelude::*,
+//! };
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Todos(Vec<String>);
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Query(String);
+//!
+//! #[derive(PartialEq)]
+//! struct Matching(Vec<String>);
+//!
+//! impl Compute for Matching {
+//!     fn compute(cx: &Tracker) -> Self {
+//!         let query = cx.get::<Query>();
+//!         let todos = cx.get::<Todos>();
+//!         let matching = todos.0.iter().filter(|todo| todo.contains(&query.0));
+//!
+//!         Self(matching.cloned().collect())
+//!     }
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! cx.set(Todos(vec!["Write docs".into(), "Review".into()]));
+//! let matching = Dispatch::<Computed<Matching>>::new(&cx);
+//!
+//! cx.set(Query("docs".into()));
+//! // Computed here, not when `Query` changed.
+//! assert_eq!(matching.get().0, vec!["Write docs".to_string()]);
+//! ```
+use std::{
+    any::{Any, TypeId},
+    cell::OnceCell,
+    marker::PhantomData,
+    ops::Deref,
+    rc::Rc,
+};
+
+use crate::{context::WeakContext, mrc::Mrc, store::Store, Context};
+
+/// Subscriptions to the stores a value was computed from, by type id.
+type Subscriptions = Vec<(TypeId, Box<dyn Any>)>;
+
+/// A value computed from other stores. See the [module docs](self).
+///
+/// Values are compared to the previous one, so subscribers are only notified when it changes.
+pub trait Compute: PartialEq + 'static {
+    /// Compute the value. Stores read through `cx` are tracked as dependencies.
+    fn compute(cx: &Tracker) -> Self;
+}
+
+/// Reads stores while computing a [Compute] value, recording them as dependencies.
+pub struct Tracker {
+    cx: Context,
+    subscriptions: Mrc<Subscriptions>,
+    invalidate: Rc<dyn Fn()>,
+}
+
+impl std::fmt::Debug for Tracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tracker").finish()
+    }
+}
+
+impl Tracker {
+    /// Get current state of `S`. The computed value is invalidated when it changes.
+    pub fn get<S: Store>(&self) -> Rc<S> {
+        let type_id = TypeId::of::<S>();
+        let tracked = self
+            .subscriptions
+            .borrow()
+            .iter()
+            .any(|(id, _)| *id == type_id);
+        if !tracked {
+            let invalidate = self.invalidate.clone();
+            let id = self.cx.subscribe_passive(move |_: Rc<S>| invalidate());
+            self.subscriptions
+                .borrow_mut()
+                .push((type_id, Box::new(id)));
+        }
+
+        self.cx.get::<S>()
+    }
+
+    /// The context stores are read from.
+    pub fn context(&self) -> &Context {
+        &self.cx
+    }
+}
+
+/// Subscriptions to the dependencies of the current value of `Computed<C>`.
+struct Dependencies<C> {
+    subscriptions: Subscriptions,
+    _marker: PhantomData<C>,
+}
+
+impl<C> Default for Dependencies<C> {
+    fn default() -> Self {
+        Self {
+            subscriptions: Default::default(),
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// A store holding a lazily computed `C`. The value is computed when first read with [Self::get]
+/// or [Deref], not by [Dispatch::get](crate::Dispatch::get), which only returns the store.
+///
+/// When a dependency of a value that was read changes, the value is recomputed right away if the
+/// store has subscribers, which are notified only if it differs from the previous one. Otherwise
+/// it's computed again once read.
+pub struct Computed<C> {
+    value: OnceCell<C>,
+    cx: WeakContext,
+}
+
+impl<C> std::fmt::Debug for Computed<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Computed")
+            .field("computed", &self.value.get().is_some())
+            .finish()
+    }
+}
+
+impl<C: Compute> Computed<C> {
+    /// The value, computed now if it hasn't been yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value must be computed after its context was dropped.
+    pub fn get(&self) -> &C {
+        self.value.get_or_init(|| {
+            let cx = self
+                .cx
+                .upgrade()
+                .expect("Context dropped before computed value was read");
+            let (value, subscriptions) = Self::compute(&cx);
+            // An outdated value doesn't replace the dependencies of the current one.
+            let current = cx
+                .peek::<Self>()
+                .is_some_and(|current| std::ptr::eq(Rc::as_ptr(&current), self));
+            if current {
+                Self::set_dependencies(&cx, subscriptions);
+            }
+
+            value
+        })
+    }
+
+    /// Whether the value has been computed.
+    pub fn is_computed(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Compute the value, returning it with subscriptions to its dependencies.
+    fn compute(cx: &Context) -> (C, Subscriptions) {
+        let invalidate = {
+            let cx = cx.downgrade();
+            Rc::new(move || {
+                if let Some(cx) = cx.upgrade() {
+                    Self::invalidate(&cx);
+                }
+            })
+        };
+        let tracker = Tracker {
+            cx: cx.clone(),
+            subscriptions: Default::default(),
+            invalidate,
+        };
+        let value = C::compute(&tracker);
+        let subscriptions = std::mem::take(&mut *tracker.subscriptions.borrow_mut());
+
+        (value, subscriptions)
+    }
+
+    fn set_dependencies(cx: &Context, subscriptions: Subscriptions) {
+        let old = cx
+            .owner::<Self>()
+            .internal::<Dependencies<C>>()
+            .with_mut(|deps| std::mem::replace(&mut deps.subscriptions, subscriptions));
+        // Dropped outside the borrow, as unsubscribing may run arbitrary code.
+        drop(old);
+    }
+
+    /// Called when a dependency of the current value changed.
+    fn invalidate(cx: &Context) {
+        let Some(current) = cx.peek::<Self>() else {
+            return;
+        };
+        // Nothing to do until it's read again.
+        let Some(old) = current.value.get() else {
+            return;
+        };
+
+        if cx.subscriber_count::<Self>() == 0 {
+            cx.reset::<Self>();
+            return;
+        }
+
+        let (value, subscriptions) = Self::compute(cx);
+        Self::set_dependencies(cx, subscriptions);
+        if value != *old {
+            cx.set(Self {
+                value: OnceCell::from(value),
+                cx: cx.downgrade(),
+            });
+        }
+    }
+}
+
+impl<C: Compute> Deref for Computed<C> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<C: Compute> Store for Computed<C> {
+    fn new(cx: &Context) -> Self {
+        Self {
+            value: OnceCell::new(),
+            cx: cx.downgrade(),
+        }
+    }
+
+    fn should_notify(&self, _old: &Self) -> bool {
+        // Only replaced when invalidated, or recomputed to a different value.
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::Dispatch;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState2(u32);
+    impl Store for TestState2 {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    thread_local! {
+        static COMPUTED: Cell<u32> = const { Cell::new(0) };
+    }
+
+    fn computed() -> u32 {
+        COMPUTED.with(Cell::get)
+    }
+
+    /// `TestState` doubled, or `TestState2` when `TestState` is 0.
+    #[derive(PartialEq)]
+    struct Doubled(u32);
+    impl Compute for Doubled {
+        fn compute(cx: &Tracker) -> Self {
+            COMPUTED.with(|count| count.set(count.get() + 1));
+            match cx.get::<TestState>().0 {
+                0 => Self(cx.get::<TestState2>().0),
+                value => Self(value * 2),
+            }
+        }
+    }
+
+    #[test]
+    fn computed_only_when_read() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<Computed<Doubled>>::new(&cx);
+        let start = computed();
+
+        cx.set(TestState(1));
+        cx.set(TestState(2));
+        assert_eq!(computed(), start);
+
+        assert_eq!(dispatch.get().0, 4);
+        assert_eq!(dispatch.get().0, 4);
+        assert_eq!(computed(), start + 1);
+    }
+
+    #[test]
+    fn recomputed_after_dependency_changes() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<Computed<Doubled>>::new(&cx);
+        assert_eq!(dispatch.get().0, 0);
+
+        cx.set(TestState2(3));
+        assert_eq!(dispatch.get().0, 3);
+
+        cx.set(TestState(5));
+        assert_eq!(dispatch.get().0, 10);
+    }
+
+    #[test]
+    fn dependencies_are_tracked_per_computation() {
+        let cx = Context::new();
+        cx.set(TestState(1));
+        let dispatch = Dispatch::<Computed<Doubled>>::new(&cx);
+        assert_eq!(dispatch.get().0, 2);
+
+        // Not read by the last computation.
+        cx.set(TestState2(3));
+        assert!(dispatch.get().is_computed());
+
+        cx.set(TestState(2));
+        assert!(!dispatch.get().is_computed());
+    }
+
+    #[test]
+    fn subscribers_are_notified_of_invalidation() {
+        let cx = Context::new();
+        let values = Mrc::new(Vec::new());
+        let _dispatch = {
+            let values = values.clone();
+            Dispatch::<Computed<Doubled>>::new(&cx)
+                .subscribe(move |state: Rc<Computed<Doubled>>| values.borrow_mut().push(state.0))
+        };
+
+        cx.set(TestState(1));
+        cx.set(TestState(2));
+
+        assert_eq!(*values.borrow(), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn subscribers_are_not_notified_of_equal_values() {
+        let cx = Context::new();
+        cx.set(TestState(1));
+        let values = Mrc::new(Vec::new());
+        let _dispatch = {
+            let values = values.clone();
+            Dispatch::<Computed<Doubled>>::new(&cx)
+                .subscribe(move |state: Rc<Computed<Doubled>>| values.borrow_mut().push(state.0))
+        };
+
+        // Falls back to `TestState2`, which is also 2.
+        cx.set(TestState2(2));
+        cx.set(TestState(0));
+        cx.set(TestState(2));
+
+        assert_eq!(*values.borrow(), vec![2, 4]);
+    }
+}
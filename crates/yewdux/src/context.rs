@@ -1,10 +1,25 @@
-use std::rc::Rc;
+use std::{
+    any::TypeId,
+    collections::VecDeque,
+    marker::PhantomData,
+    rc::{Rc, Weak},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
+    action_log::{ActionLog, Log},
     anymap::AnyMap,
-    mrc::Mrc,
-    store::{Reducer, Store},
+    effect::{Effect, EffectReducer},
+    graph,
+    introspection::{self, Registry, StoreInfo},
+    middleware::{self, Middleware, Middlewares},
+    mrc::{Mrc, WeakMrc},
+    scheduler::{self, NotifyScheduler, Scheduling, Timer},
+    selector::Selection,
+    store::{Reducer, Store, TryReducer},
     subscriber::{Callable, SubscriberId, Subscribers},
+    transaction::Transaction,
 };
 
 pub(crate) struct Entry<S> {
@@ -20,18 +35,87 @@ impl<S> Clone for Entry<S> {
 }
 
 impl<S: Store> Entry<S> {
-    /// Apply a function to state, returning if it should notify subscribers or not.
-    pub(crate) fn reduce<R: Reducer<S>>(&self, reducer: R) -> bool {
+    /// Apply a function to state, returning if it should notify subscribers or not, or `None` if
+    /// middleware rejected the change.
+    pub(crate) fn reduce<R: Reducer<S>>(&self, cx: &Context, reducer: R) -> Option<bool> {
         let old = Rc::clone(&self.store.borrow());
         // Apply the reducer.
         let new = reducer.apply(Rc::clone(&old));
-        // Update to new state.
+
+        self.commit(cx, old, new)
+    }
+
+    /// Like [Self::reduce], but state is only updated if the reducer succeeds.
+    pub(crate) fn try_reduce<R: TryReducer<S>>(
+        &self,
+        cx: &Context,
+        reducer: R,
+    ) -> Result<Option<bool>, R::Error> {
+        let old = Rc::clone(&self.store.borrow());
+        let new = reducer.try_apply(Rc::clone(&old))?;
+
+        Ok(self.commit(cx, old, new))
+    }
+
+    /// Run middleware, then update to new state. Returns whether or not subscribers should be
+    /// notified, or `None` if middleware rejected the change.
+    fn commit(&self, cx: &Context, old: Rc<S>, new: Rc<S>) -> Option<bool> {
+        let should_notify = new.should_notify(&old);
+        let (new, should_notify) = middleware::run(cx, &old, new, should_notify)?;
+
         *self.store.borrow_mut() = new;
-        // Return whether or not subscribers should be notified.
-        self.store.borrow().should_notify(&old)
+
+        if should_notify {
+            cx.internal::<Registry>()
+                .with_mut(|registry| registry.changed::<S>());
+        }
+
+        Some(should_notify)
     }
 }
 
+/// Creates store `S` in place of [Store::new], for stores that are always local to a context,
+/// like the instances of a [store family](crate::family).
+pub(crate) struct Constructor<S>(Rc<dyn Fn(&Context) -> S>);
+
+/// Marks store `S` as [provided](Context::provide), so it stays local after being removed.
+struct Provided<S>(PhantomData<S>);
+
+/// Sends the latest state of a store to its subscribers.
+type Notify = fn(&Context);
+
+/// Runs effects returned by a reducer of a store, once its subscribers have been notified.
+type ScheduleEffects = fn(&Context, Vec<Effect>);
+
+/// Tracks batches in progress, and the stores that changed during them.
+#[derive(Default)]
+pub(crate) struct Batch {
+    depth: usize,
+    pending: Vec<(TypeId, Notify)>,
+    effects: Vec<(ScheduleEffects, Vec<Effect>)>,
+}
+
+/// How changes made while subscribers are being notified are delivered. See
+/// [Context::set_dispatch_mode].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// Subscribers are notified of a change immediately, even while they are being notified of an
+    /// earlier one. Subscribers later in the earlier round may then receive states out of order.
+    #[default]
+    Immediate,
+    /// Changes made while subscribers are being notified are queued, and delivered in order once
+    /// the current round completes. Every subscriber receives every state, in order.
+    Queued,
+}
+
+/// Notifications waiting for the current round to complete, in [DispatchMode::Queued].
+#[derive(Default)]
+pub(crate) struct Queue {
+    mode: DispatchMode,
+    notifying: bool,
+    pending: VecDeque<Box<dyn FnOnce()>>,
+}
+
 /// Execution context for a dispatch
 ///
 /// # Example
@@ -49,6 +133,27 @@ impl<S: Store> Entry<S> {
 #[derive(Clone, Default, PartialEq)]
 pub struct Context {
     inner: Mrc<AnyMap>,
+    parent: Option<Rc<Context>>,
+}
+
+/// Reference to a [Context] that doesn't keep its stores, or those of its parents, alive.
+pub(crate) struct WeakContext {
+    inner: WeakMrc<AnyMap>,
+    parent: Option<Weak<Context>>,
+}
+
+impl WeakContext {
+    pub(crate) fn upgrade(&self) -> Option<Context> {
+        let parent = match &self.parent {
+            Some(parent) => Some(parent.upgrade()?),
+            None => None,
+        };
+
+        Some(Context {
+            inner: self.inner.upgrade()?,
+            parent,
+        })
+    }
 }
 
 impl Context {
@@ -56,6 +161,123 @@ impl Context {
         Default::default()
     }
 
+    /// Create a context that shares all stores with this one, except those [provided](Self::provide)
+    /// in the child.
+    ///
+    /// Everything about a store is resolved by the context that owns it: the nearest context that
+    /// provides it, or the root context otherwise. This includes subscribers, listeners,
+    /// middleware, and the [ActionLog].
+    ///
+    /// ```
+    /// use yewdux::prelude::*;
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Theme(&'static str);
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Counter(usize);
+    ///
+    /// let parent = yewdux::Context::new();
+    /// let child = parent.child();
+    /// child.provide(Theme("dark"));
+    ///
+    /// child.set(Counter(1));
+    /// assert_eq!(parent.get::<Counter>().0, 1);
+    /// assert_eq!(parent.get::<Theme>().0, "");
+    /// assert_eq!(child.get::<Theme>().0, "dark");
+    /// ```
+    pub fn child(&self) -> Self {
+        Self {
+            inner: Default::default(),
+            parent: Some(Rc::new(self.clone())),
+        }
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakContext {
+        WeakContext {
+            inner: self.inner.downgrade(),
+            parent: self.parent.as_ref().map(Rc::downgrade),
+        }
+    }
+
+    /// The context this was created from with [Self::child], if any.
+    pub fn parent(&self) -> Option<&Context> {
+        self.parent.as_deref()
+    }
+
+    /// The context at the top of this one's [parent](Self::parent) chain.
+    pub(crate) fn root(&self) -> &Self {
+        let mut cx = self;
+        while let Some(parent) = &cx.parent {
+            cx = parent;
+        }
+
+        cx
+    }
+
+    /// Whether both are the same context. Unlike `==`, this is still true after either changed.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        self.inner.ptr_eq(&other.inner)
+    }
+
+    /// Make store `S` local to this context, initialized with `value`. Stores should be provided
+    /// before they are used, as existing subscribers of a parent's store are not moved to this one.
+    ///
+    /// If `S` is already local to this context, this is the same as [Self::set].
+    pub fn provide<S: Store>(&self, value: S) {
+        self.inner
+            .with_mut(|x| x.insert(Provided::<S>(PhantomData)));
+        let mut value = Some(value);
+        let (_, created) = self.get_or_init_entry(|_| value.take().expect("value already taken"));
+        match value {
+            Some(value) => self.set(value),
+            None => self.register::<S>(created),
+        }
+    }
+
+    /// The context that owns store `S`: the nearest one where it's local, or the root context.
+    pub(crate) fn owner<S: Store>(&self) -> &Self {
+        let mut cx = self;
+        while let Some(parent) = &cx.parent {
+            if cx.is_local::<S>() {
+                break;
+            }
+
+            cx = parent;
+        }
+
+        cx
+    }
+
+    /// Whether `S` is initialized in this context, or will be when used.
+    fn is_local<S: Store>(&self) -> bool {
+        let inner = self.inner.borrow();
+        inner
+            .get::<Mrc<Option<Entry<S>>>>()
+            .is_some_and(|entry| entry.borrow().is_some())
+            || inner.get::<Constructor<S>>().is_some()
+            || inner.get::<Provided<S>>().is_some()
+    }
+
+    /// Make `S` local to this context, always created with `new_store` instead of [Store::new].
+    pub(crate) fn set_constructor<S: Store>(&self, new_store: impl Fn(&Self) -> S + 'static) {
+        self.inner
+            .with_mut(|x| x.insert(Constructor::<S>(Rc::new(new_store))));
+    }
+
+    /// Create store `S` with its constructor in this context, or [Store::new].
+    fn new_store<S: Store>(&self) -> S {
+        let constructor = self
+            .inner
+            .borrow()
+            .get::<Constructor<S>>()
+            .map(|constructor| Rc::clone(&constructor.0));
+        match constructor {
+            Some(new_store) => new_store(self),
+            None => S::new(self),
+        }
+    }
+
     #[cfg(any(doc, feature = "doctests", target_arch = "wasm32"))]
     pub fn global() -> Self {
         thread_local! {
@@ -67,6 +289,14 @@ impl Context {
             .expect("CONTEXTS thread local key init failed")
     }
 
+    /// Get internal state for this context, initializing it with `Default` if it doesn't exist.
+    ///
+    /// This state is never exposed as a store, so it has no subscribers and is never notified.
+    pub(crate) fn internal<T: Default + 'static>(&self) -> Mrc<T> {
+        self.inner
+            .with_mut(|x| x.entry::<Mrc<T>>().or_insert_with(Default::default).clone())
+    }
+
     /// Initialize a store using a custom constructor. `Store::new` will not be called in this
     /// case. If already initialized, the custom constructor will not be called.
     pub fn init<S: Store, F: FnOnce(&Self) -> S>(&self, new_store: F) {
@@ -76,6 +306,34 @@ impl Context {
     /// Get or initialize a store using a custom constructor. `Store::new` will not be called in
     /// this case. If already initialized, the custom constructor will not be called.
     pub(crate) fn get_or_init<S: Store, F: FnOnce(&Self) -> S>(&self, new_store: F) -> Entry<S> {
+        let cx = self.owner::<S>();
+        let (entry, created) = cx.get_or_init_entry(new_store);
+        cx.register::<S>(created);
+
+        entry
+    }
+
+    fn register<S: Store>(&self, created: bool) {
+        if created {
+            self.internal::<Registry>()
+                .with_mut(|registry| registry.register::<S>());
+        }
+    }
+
+    /// Like [Self::get_or_init], but for stores used internally, which are never listed in
+    /// [Self::stores]. These are always local to this context.
+    pub(crate) fn get_or_init_hidden<S: Store, F: FnOnce(&Self) -> S>(
+        &self,
+        new_store: F,
+    ) -> Entry<S> {
+        self.get_or_init_entry(new_store).0
+    }
+
+    /// Get or initialize a store, returning whether it was just created.
+    fn get_or_init_entry<S: Store, F: FnOnce(&Self) -> S>(
+        &self,
+        new_store: F,
+    ) -> (Entry<S>, bool) {
         // Get context, or None if it doesn't exist.
         //
         // We use an option here because a new Store should not be created during this borrow. We
@@ -108,21 +366,210 @@ impl Context {
             .clone()
             .expect("Context not initialized");
 
-        entry
+        (entry, !exists)
     }
 
     /// Get or initialize a store with a default Store::new implementation.
     pub(crate) fn get_or_init_default<S: Store>(&self) -> Entry<S> {
-        self.get_or_init(S::new)
+        self.get_or_init(Self::new_store::<S>)
     }
 
     pub fn reduce<S: Store, R: Reducer<S>>(&self, r: R) {
-        let entry = self.get_or_init_default::<S>();
-        let should_notify = entry.reduce(r);
+        self.reduce_committed(r, || {});
+    }
+
+    /// Like [Self::reduce], returning whether the change was committed, or rejected by
+    /// [middleware](crate::middleware). `on_commit` runs once it's committed, before subscribers
+    /// are notified.
+    fn reduce_committed<S: Store, R: Reducer<S>>(&self, r: R, on_commit: impl FnOnce()) -> bool {
+        let cx = self.owner::<S>();
+        let entry = cx.get_or_init_default::<S>();
+        let Some(should_notify) = entry.reduce(cx, r) else {
+            return false;
+        };
 
+        on_commit();
         if should_notify {
-            let state = Rc::clone(&entry.store.borrow());
-            self.notify_subscribers(state)
+            cx.notify_changed(&entry);
+        }
+
+        true
+    }
+
+    /// Apply all changes made in `f`, then notify subscribers once for every store that changed.
+    ///
+    /// Subscribers receive the latest state of their store after `f` returns. Batches may be
+    /// nested, in which case notifications are sent when the outermost batch completes. Changes
+    /// made by subscribers while notifications are being sent are batched too, and delivered in
+    /// the following round.
+    ///
+    /// Batches also cover stores owned by [parent](Self::child) contexts.
+    ///
+    /// ```
+    /// use yewdux::prelude::*;
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Counter(usize);
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Total(usize);
+    ///
+    /// let cx = yewdux::Context::new();
+    /// cx.batch(|cx| {
+    ///     cx.reduce_mut(|counter: &mut Counter| counter.0 += 1);
+    ///     cx.reduce_mut(|total: &mut Total| total.0 += 1);
+    ///     // Subscribers of `Counter` and `Total` haven't been notified yet.
+    /// });
+    /// ```
+    pub fn batch<R, F: FnOnce(&Self) -> R>(&self, f: F) -> R {
+        let contexts = std::iter::successors(Some(self), |cx| cx.parent());
+        for cx in contexts.clone() {
+            cx.internal::<Batch>().with_mut(|batch| batch.depth += 1);
+        }
+
+        let result = f(self);
+
+        // Children first, so changes their subscribers make to parent stores are still batched.
+        for cx in contexts {
+            let depth = cx.internal::<Batch>().with_mut(|batch| {
+                batch.depth -= 1;
+                batch.depth
+            });
+            if depth == 0 {
+                cx.flush_batch();
+            }
+        }
+
+        result
+    }
+
+    /// Apply changes to multiple stores atomically. If `f` returns an error, every store changed
+    /// through the [Transaction] is restored to its previous state, and nobody is notified.
+    /// Otherwise subscribers are notified once `f` returns, as with [Self::batch].
+    ///
+    /// ```
+    /// use yewdux::prelude::*;
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Account(i32);
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Savings(i32);
+    ///
+    /// let cx = yewdux::Context::new();
+    /// let result = cx.transaction(|tx| {
+    ///     tx.reduce_mut(|savings: &mut Savings| savings.0 += 10);
+    ///     tx.reduce_mut(|account: &mut Account| account.0 -= 10);
+    ///
+    ///     if tx.get::<Account>().0 < 0 {
+    ///         return Err("insufficient funds");
+    ///     }
+    ///
+    ///     Ok(())
+    /// });
+    ///
+    /// assert!(result.is_err());
+    /// assert_eq!(cx.get::<Savings>().0, 0);
+    /// assert_eq!(cx.get::<Account>().0, 0);
+    /// ```
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Transaction) -> Result<T, E>,
+    {
+        self.batch(|cx| {
+            let tx = Transaction::new(cx);
+            let result = f(&tx);
+            if result.is_err() {
+                tx.rollback();
+            }
+
+            result
+        })
+    }
+
+    /// Whether changes are currently being batched.
+    pub(crate) fn is_batching(&self) -> bool {
+        self.internal::<Batch>().borrow().depth > 0
+    }
+
+    /// Notify subscribers of `S` when the current batch completes. Notifications for the same store
+    /// are coalesced.
+    pub(crate) fn defer_notify<S: Store>(&self) {
+        let type_id = TypeId::of::<S>();
+        self.owner::<S>().internal::<Batch>().with_mut(|batch| {
+            if !batch.pending.iter().any(|(id, _)| *id == type_id) {
+                batch.pending.push((type_id, Self::deliver_latest::<S>));
+            }
+        });
+    }
+
+    /// Whether a notification for `S` is waiting for the current batch to complete.
+    pub(crate) fn is_pending<S: Store>(&self) -> bool {
+        let type_id = TypeId::of::<S>();
+        self.owner::<S>()
+            .internal::<Batch>()
+            .borrow()
+            .pending
+            .iter()
+            .any(|(id, _)| *id == type_id)
+    }
+
+    /// Cancel a pending notification for `S`.
+    pub(crate) fn cancel_notify<S: Store>(&self) {
+        let type_id = TypeId::of::<S>();
+        self.owner::<S>()
+            .internal::<Batch>()
+            .with_mut(|batch| batch.pending.retain(|(id, _)| *id != type_id));
+    }
+
+    fn deliver_latest<S: Store>(&self) {
+        self.deliver(self.get::<S>());
+    }
+
+    /// Send all pending notifications, in the order stores were first changed.
+    fn flush_batch(&self) {
+        let batch = self.internal::<Batch>();
+        // Keep batching while notifying, so changes made by subscribers are coalesced into the next
+        // round instead of being sent immediately. In queued mode they're queued instead, so every
+        // state is delivered.
+        let batching = self.dispatch_mode() == DispatchMode::Immediate;
+        if batching {
+            batch.with_mut(|batch| batch.depth += 1);
+        }
+        loop {
+            let pending = batch.with_mut(|batch| std::mem::take(&mut batch.pending));
+            if !pending.is_empty() {
+                // Derived stores are brought up to date before anyone is notified.
+                let changed = pending.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+                let derived = graph::update(self, &changed)
+                    .into_iter()
+                    .filter(|(id, cx, _)| !(cx.ptr_eq(self) && changed.contains(id)))
+                    .collect::<Vec<_>>();
+
+                self.notify_round(|| {
+                    for (_, notify) in pending {
+                        notify(self);
+                    }
+                    for (_, cx, notify) in derived {
+                        notify(&cx);
+                    }
+                });
+
+                continue;
+            }
+
+            // Effects run once everyone has been notified.
+            let effects = batch.with_mut(|batch| std::mem::take(&mut batch.effects));
+            if effects.is_empty() {
+                break;
+            }
+
+            for (schedule, effects) in effects {
+                schedule(self, effects);
+            }
+        }
+        if batching {
+            batch.with_mut(|batch| batch.depth -= 1);
         }
     }
 
@@ -133,6 +580,256 @@ impl Context {
         });
     }
 
+    /// Apply an [EffectReducer], then run its effects once subscribers have been notified. When
+    /// batching, effects run after the batch completes, and when notifications are delayed by a
+    /// [NotifyScheduler], after the delayed notification is sent. Effects are dropped if
+    /// [middleware](crate::middleware) rejects the change.
+    pub fn reduce_effects<S: Store, R: EffectReducer<S>>(&self, r: R) {
+        let mut effects = Vec::new();
+        let committed = self.reduce_committed(
+            |state| {
+                let (state, new_effects) = r.apply(state);
+                effects = new_effects;
+                state
+            },
+            || {},
+        );
+        if !committed {
+            return;
+        }
+
+        if self.is_batching() {
+            self.internal::<Batch>()
+                .with_mut(|batch| batch.effects.push((Self::schedule_effects::<S>, effects)));
+        } else {
+            self.schedule_effects::<S>(effects);
+        }
+    }
+
+    /// Run effects once subscribers of `S` are notified, waiting for a notification scheduled by
+    /// its [NotifyScheduler] if there is one.
+    fn schedule_effects<S: Store>(&self, effects: Vec<Effect>) {
+        let effects = scheduler::defer_effects::<S>(self.owner::<S>(), effects);
+        self.run_effects(effects);
+    }
+
+    /// Run effects once pending notifications are delivered. In [DispatchMode::Queued] they're
+    /// queued behind notifications sent so far.
+    pub(crate) fn run_effects(&self, effects: Vec<Effect>) {
+        if effects.is_empty() {
+            return;
+        }
+
+        if self.dispatch_mode() == DispatchMode::Immediate {
+            for effect in effects {
+                effect.run(self);
+            }
+            return;
+        }
+
+        let cx = self.clone();
+        let queue = self.root().internal::<Queue>();
+        self.notify_round(|| {
+            queue.with_mut(|queue| {
+                queue.pending.push_back(Box::new(move || {
+                    for effect in effects {
+                        effect.run(&cx);
+                    }
+                }))
+            })
+        });
+    }
+
+    /// Like [Self::reduce], but the reducer is also recorded in the [ActionLog] of the context that
+    /// owns `S`. Changes rejected by [middleware](crate::middleware) are not recorded.
+    pub fn reduce_recorded<S, R>(&self, r: R)
+    where
+        S: Store,
+        R: Reducer<S> + Serialize + DeserializeOwned,
+    {
+        let action = ActionLog::prepare::<S, R>(&r);
+        self.reduce_committed(r, || {
+            if let Some(action) = action {
+                self.owner::<S>().action_log().record(action);
+            }
+        });
+    }
+
+    /// Get the log of actions recorded in this context.
+    pub fn action_log(&self) -> ActionLog {
+        ActionLog(self.internal::<Log>())
+    }
+
+    /// Apply a [TryReducer]. If it fails, state is left untouched, and subscribers are not
+    /// notified.
+    pub fn try_reduce<S: Store, R: TryReducer<S>>(&self, r: R) -> Result<(), R::Error> {
+        let cx = self.owner::<S>();
+        let entry = cx.get_or_init_default::<S>();
+        let should_notify = entry.try_reduce(cx, r)?;
+
+        if should_notify == Some(true) {
+            cx.notify_changed(&entry);
+        }
+
+        Ok(())
+    }
+
+    /// Like [Self::reduce_mut], but state is only updated if `f` succeeds.
+    pub fn try_reduce_mut<S, E, F>(&self, f: F) -> Result<(), E>
+    where
+        S: Store + Clone,
+        F: FnOnce(&mut S) -> Result<(), E>,
+    {
+        self.try_reduce(|mut state| {
+            // The entry still holds a reference to current state, so this always mutates a copy.
+            f(Rc::make_mut(&mut state))?;
+            Ok(state)
+        })
+    }
+
+    /// Set how changes made while subscribers are being notified are delivered. The mode is shared
+    /// by every context created from the same root with [Self::child].
+    ///
+    /// With [DispatchMode::Queued], a change made by a subscriber or listener is applied
+    /// immediately, but its subscribers are only notified after everyone has been notified of the
+    /// current change. When a [batch](Self::batch) completes, changes made by its subscribers are
+    /// queued too, instead of being batched.
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    ///
+    /// use yewdux::{context::DispatchMode, prelude::*};
+    ///
+    /// #[derive(Clone, PartialEq, Default, Store)]
+    /// struct Counter(u32);
+    ///
+    /// let cx = yewdux::Context::new();
+    /// cx.set_dispatch_mode(DispatchMode::Queued);
+    ///
+    /// let seen = Rc::new(std::cell::RefCell::new(Vec::new()));
+    /// let _first = {
+    ///     let cx = cx.clone();
+    ///     cx.clone().subscribe_silent(move |counter: Rc<Counter>| {
+    ///         if counter.0 == 1 {
+    ///             cx.set(Counter(2));
+    ///         }
+    ///     })
+    /// };
+    /// let _second = {
+    ///     let seen = seen.clone();
+    ///     cx.subscribe_silent(move |counter: Rc<Counter>| seen.borrow_mut().push(counter.0))
+    /// };
+    ///
+    /// cx.set(Counter(1));
+    /// assert_eq!(*seen.borrow(), vec![1, 2]);
+    /// ```
+    pub fn set_dispatch_mode(&self, mode: DispatchMode) {
+        self.root()
+            .internal::<Queue>()
+            .with_mut(|queue| queue.mode = mode);
+    }
+
+    /// Set when subscribers of stores without their own [NotifyScheduler] are notified. Shared by
+    /// every context created from the same root with [Self::child].
+    pub fn set_notify_scheduler(&self, scheduler: NotifyScheduler) {
+        self.root()
+            .internal::<Scheduling>()
+            .with_mut(|scheduling| scheduling.default = scheduler);
+    }
+
+    /// Set when subscribers of `S` are notified, overriding [Self::set_notify_scheduler].
+    pub fn set_store_scheduler<S: Store>(&self, scheduler: NotifyScheduler) {
+        self.root()
+            .internal::<Scheduling>()
+            .with_mut(|scheduling| scheduling.stores.insert(TypeId::of::<S>(), scheduler));
+    }
+
+    /// Set the [Timer] that runs delayed notifications. Defaults to [PlatformTimer].
+    ///
+    /// [PlatformTimer]: crate::scheduler::PlatformTimer
+    pub fn set_timer<T: Timer>(&self, timer: T) {
+        self.root()
+            .internal::<Scheduling>()
+            .with_mut(|scheduling| scheduling.timer = Some(Rc::new(timer)));
+    }
+
+    /// Current [DispatchMode].
+    pub fn dispatch_mode(&self) -> DispatchMode {
+        self.root().internal::<Queue>().borrow().mode
+    }
+
+    /// Like [Self::reduce], but subscribers are not notified, and derived stores are not updated.
+    /// Returns whether subscribers should be notified.
+    pub(crate) fn reduce_silent<S: Store, R: Reducer<S>>(&self, r: R) -> bool {
+        let cx = self.owner::<S>();
+        cx.get_or_init_default::<S>().reduce(cx, r) == Some(true)
+    }
+
+    /// Notify subscribers of a change to `entry`, or wait until the current batch completes.
+    /// Stores derived from it are updated first.
+    fn notify_changed<S: Store>(&self, entry: &Entry<S>) {
+        if self.is_batching() {
+            self.defer_notify::<S>();
+            return;
+        }
+
+        let derived = graph::update(self, &[TypeId::of::<S>()]);
+        let state = Rc::clone(&entry.store.borrow());
+        self.deliver(state);
+        for (_, cx, deliver) in derived {
+            deliver(&cx);
+        }
+    }
+
+    /// Notify subscribers of `state`, according to the [NotifyScheduler] and [DispatchMode].
+    pub(crate) fn deliver<S: Store>(&self, state: Rc<S>) {
+        let (scheduler, timer) = self
+            .root()
+            .internal::<Scheduling>()
+            .with_mut(|scheduling| (scheduling.scheduler::<S>(), scheduling.timer()));
+        if scheduler != NotifyScheduler::Immediate {
+            scheduler::schedule::<S>(self, scheduler, timer.as_ref());
+            return;
+        }
+
+        self.deliver_now(state);
+    }
+
+    /// Notify subscribers of `state` now, or once earlier notifications are delivered in
+    /// [DispatchMode::Queued].
+    pub(crate) fn deliver_now<S: Store>(&self, state: Rc<S>) {
+        let queue = self.root().internal::<Queue>();
+        if queue.borrow().mode == DispatchMode::Immediate {
+            self.notify_subscribers(state);
+            return;
+        }
+
+        let cx = self.clone();
+        self.notify_round(|| {
+            queue.with_mut(|queue| {
+                queue
+                    .pending
+                    .push_back(Box::new(move || cx.notify_subscribers(state)))
+            })
+        });
+    }
+
+    /// Run `f` as a round of notifications. In [DispatchMode::Queued], notifications queued during
+    /// it are then delivered in order, unless a round is already in progress to deliver them.
+    fn notify_round(&self, f: impl FnOnce()) {
+        let queue = self.root().internal::<Queue>();
+        let notifying = queue.with_mut(|queue| std::mem::replace(&mut queue.notifying, true));
+        f();
+        if notifying {
+            return;
+        }
+
+        while let Some(notify) = queue.with_mut(|queue| queue.pending.pop_front()) {
+            notify();
+        }
+        queue.with_mut(|queue| queue.notifying = false);
+    }
+
     /// Set state to given value.
     pub fn set<S: Store>(&self, value: S) {
         self.reduce(move |_| value.into());
@@ -143,10 +840,82 @@ impl Context {
         Rc::clone(&self.get_or_init_default::<S>().store.borrow())
     }
 
+    /// Reinitialize a store with [Store::new], notifying subscribers if it changed. Instances of a
+    /// [store family](crate::family) are created again with their key instead.
+    pub fn reset<S: Store>(&self) {
+        let cx = self.owner::<S>();
+        cx.reduce(|_: Rc<S>| cx.new_store::<S>().into());
+    }
+
+    /// Drop a store and its listeners.
+    ///
+    /// Subscribers are not notified, and stay subscribed until their [SubscriberId] is dropped. The
+    /// store is initialized again with [Store::new] the next time it is accessed, for example by a
+    /// live [Dispatch](crate::Dispatch), and subscribers are notified of changes as usual from then
+    /// on. Instances of a [store family](crate::family) are created again with their key, and
+    /// [provided](Self::provide) stores stay local to their context.
+    ///
+    /// Use [Self::reset] instead for stores that are still in use.
+    pub fn remove<S: Store>(&self) {
+        let cx = self.owner::<S>();
+        cx.cancel_notify::<S>();
+        crate::listener::remove_listeners::<S>(cx);
+
+        let maybe_entry = cx.inner.with_mut(|x| {
+            x.entry::<Mrc<Option<Entry<S>>>>()
+                .or_insert_with(|| None.into())
+                .clone()
+        });
+        // Dropped outside of borrow, in case state accesses the context when dropped.
+        let _entry = maybe_entry.borrow_mut().take();
+
+        cx.internal::<Registry>()
+            .with_mut(|registry| registry.unregister::<S>());
+    }
+
+    /// Information about every store owned by this context, in the order they were initialized.
+    /// Stores shared with a [parent](Self::child) context are not included.
+    pub fn stores(&self) -> impl Iterator<Item = StoreInfo> {
+        introspection::stores(self).into_iter()
+    }
+
+    /// Information about store `S`, or `None` if it isn't initialized.
+    pub fn store_info<S: Store>(&self) -> Option<StoreInfo> {
+        let type_name = std::any::type_name::<S>();
+        self.owner::<S>()
+            .stores()
+            .find(|info| info.type_name == type_name)
+    }
+
+    /// Include a snapshot of store `S` in its [StoreInfo].
+    pub fn enable_snapshot<S: Store + Serialize>(&self) {
+        self.owner::<S>()
+            .internal::<Registry>()
+            .with_mut(|registry| registry.enable_snapshot::<S>());
+    }
+
+    /// Number of changes subscribers have been notified of in this context. Increases by one with
+    /// every change, so may be compared with [StoreInfo::last_changed].
+    pub fn revision(&self) -> u64 {
+        self.internal::<Registry>().borrow().revision()
+    }
+
+    /// Number of subscribers of `S` that keep it active. Listeners are not included.
+    pub(crate) fn subscriber_count<S: Store>(&self) -> usize {
+        self.subscribers::<S>().active()
+    }
+
+    fn subscribers<S: Store>(&self) -> Mrc<Subscribers<S>> {
+        let entry = self
+            .owner::<S>()
+            .get_or_init_hidden(<Mrc<Subscribers<S>> as Store>::new);
+        let subscribers = Mrc::clone(&entry.store.borrow());
+        subscribers
+    }
+
     /// Send state to all subscribers.
     pub fn notify_subscribers<S: Store>(&self, state: Rc<S>) {
-        let entry = self.get_or_init_default::<Mrc<Subscribers<S>>>();
-        entry.store.borrow().notify(state);
+        self.subscribers::<S>().notify(state);
     }
 
     /// Subscribe to a store. `on_change` is called immediately, then every  time state changes.
@@ -154,18 +923,68 @@ impl Context {
         // Notify subscriber with inital state.
         on_change.call(self.get::<S>());
 
-        self.get_or_init_default::<Mrc<Subscribers<S>>>()
-            .store
-            .borrow()
-            .subscribe(on_change)
+        self.subscribers::<S>().subscribe(on_change, true)
     }
 
     /// Similar to [Self::subscribe], however state is not called immediately.
     pub fn subscribe_silent<S: Store, N: Callable<S>>(&self, on_change: N) -> SubscriberId<S> {
-        self.get_or_init_default::<Mrc<Subscribers<S>>>()
-            .store
+        self.subscribers::<S>().subscribe(on_change, true)
+    }
+
+    /// Subscribe to the value `selector` returns from `S`. `on_change` is called only when that
+    /// value changes, and not for the initial value.
+    pub fn select<S, R, F, C>(&self, selector: F, on_change: C) -> Selection<S, R>
+    where
+        S: Store,
+        R: PartialEq + 'static,
+        F: Fn(&S) -> R + 'static,
+        C: Callable<R>,
+    {
+        self.select_eq(selector, |a, b| a == b, on_change)
+    }
+
+    /// Similar to [Self::select], with a custom equality check for the selected value.
+    pub fn select_eq<S, R, F, E, C>(&self, selector: F, eq: E, on_change: C) -> Selection<S, R>
+    where
+        S: Store,
+        R: 'static,
+        F: Fn(&S) -> R + 'static,
+        E: Fn(&R, &R) -> bool + 'static,
+        C: Callable<R>,
+    {
+        Selection::new(self, selector, eq, on_change)
+    }
+
+    /// Like [Self::subscribe_silent], but the subscriber doesn't keep the store active, so
+    /// [Store::on_active] and [Store::on_inactive] aren't called for it.
+    pub(crate) fn subscribe_passive<S: Store, N: Callable<S>>(
+        &self,
+        on_change: N,
+    ) -> SubscriberId<S> {
+        self.subscribers::<S>().subscribe(on_change, false)
+    }
+
+    /// Current state of `S`, or `None` if it isn't initialized.
+    pub(crate) fn peek<S: Store>(&self) -> Option<Rc<S>> {
+        let maybe_entry = self
+            .owner::<S>()
+            .inner
+            .borrow()
+            .get::<Mrc<Option<Entry<S>>>>()?
+            .clone();
+        let state = maybe_entry
             .borrow()
-            .subscribe(on_change)
+            .as_ref()
+            .map(|entry| Rc::clone(&entry.store.borrow()));
+
+        state
+    }
+
+    /// Add [Middleware] that runs around every state change in this context. Middleware runs in the
+    /// order it was added.
+    pub fn add_middleware<M: Middleware>(&self, middleware: M) {
+        self.internal::<Middlewares>()
+            .with_mut(|middlewares| middlewares.push(middleware));
     }
 
     /// Initialize a listener
@@ -188,6 +1007,14 @@ impl Context {
     {
         crate::derived_from::derive_from_mut::<Store, Derived>(self);
     }
+
+    pub fn derived_from_many<Sources, Derived>(&self)
+    where
+        Sources: crate::derived_from::Sources,
+        Derived: crate::derived_from::DerivedFromMany<Sources>,
+    {
+        crate::derived_from::derive_from_many::<Sources, Derived>(self);
+    }
 }
 
 #[cfg(test)]
@@ -267,4 +1094,459 @@ mod tests {
 
         assert_eq!(cx.get::<TestState>().0, 2);
     }
+
+    #[test]
+    fn try_reduce_changes_state_on_success() {
+        let cx = Context::new();
+        let result: Result<(), ()> = cx.try_reduce(|_| Ok(TestState(1).into()));
+
+        assert!(result.is_ok());
+        assert_eq!(cx.get::<TestState>().0, 1);
+    }
+
+    #[test]
+    fn try_reduce_mut_leaves_state_untouched_on_error() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        let result = cx.try_reduce_mut(|state: &mut TestState| {
+            state.0 = 1;
+            Err("invalid")
+        });
+
+        assert_eq!(result, Err("invalid"));
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn batch_notifies_once_per_store() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        cx.batch(|cx| {
+            cx.reduce_mut(|state: &mut TestState| state.0 += 1);
+            cx.reduce_mut(|state: &mut TestState| state.0 += 1);
+            cx.reduce_mut(|state: &mut TestState| state.0 += 1);
+            assert_eq!(calls.get(), 0);
+        });
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(cx.get::<TestState>().0, 3);
+    }
+
+    #[test]
+    fn batch_sends_latest_state() {
+        let cx = Context::new();
+        let seen = Rc::new(Cell::new(0));
+        let _id = {
+            let seen = seen.clone();
+            cx.subscribe_silent::<TestState, _>(move |state: Rc<TestState>| seen.set(state.0))
+        };
+
+        cx.batch(|cx| {
+            cx.set(TestState(1));
+            cx.set(TestState(2));
+        });
+
+        assert_eq!(seen.get(), 2);
+    }
+
+    #[test]
+    fn batch_subscribers_see_all_changes() {
+        let cx = Context::new();
+        let seen = Rc::new(Cell::new((0, 0)));
+        let _id = {
+            let seen = seen.clone();
+            let cx2 = cx.clone();
+            cx.subscribe_silent::<TestState, _>(move |state: Rc<TestState>| {
+                seen.set((state.0, cx2.get::<TestState2>().0))
+            })
+        };
+
+        cx.batch(|cx| {
+            cx.set(TestState(1));
+            cx.set(TestState2(1));
+        });
+
+        assert_eq!(seen.get(), (1, 1));
+    }
+
+    #[test]
+    fn nested_batch_notifies_after_outermost() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        cx.batch(|cx| {
+            cx.batch(|cx| cx.reduce_mut(|state: &mut TestState| state.0 += 1));
+            assert_eq!(calls.get(), 0);
+            cx.reduce_mut(|state: &mut TestState| state.0 += 1);
+        });
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn batch_coalesces_changes_from_subscribers() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let cx2 = cx.clone();
+            cx.subscribe_silent::<TestState, _>(move |state: Rc<TestState>| {
+                cx2.set(TestState2(state.0));
+                cx2.set(TestState2(state.0 + 1));
+            })
+        };
+        let _id2 = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState2, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        cx.batch(|cx| cx.set(TestState(1)));
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(cx.get::<TestState2>().0, 2);
+    }
+
+    #[test]
+    fn batch_does_not_notify_without_changes() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        cx.batch(|cx| cx.set(TestState(0)));
+
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn reset_reinitializes_and_notifies() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+        cx.set(TestState(1));
+
+        cx.reset::<TestState>();
+
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn remove_drops_state() {
+        let cx = Context::new();
+        cx.set(TestState(1));
+        let state = cx.get::<TestState>();
+
+        cx.remove::<TestState>();
+
+        assert_eq!(Rc::strong_count(&state), 1);
+        // Initialized again on next access.
+        assert_eq!(cx.get::<TestState>().0, 0);
+    }
+
+    #[test]
+    fn remove_keeps_subscribers() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+        cx.set(TestState(1));
+
+        cx.remove::<TestState>();
+        assert_eq!(calls.get(), 1);
+
+        crate::Dispatch::<TestState>::new(&cx).set(TestState(1));
+        assert_eq!(calls.get(), 2);
+
+        drop(id);
+        cx.set(TestState(2));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn remove_drops_listeners() {
+        struct Count(Rc<Cell<u32>>);
+        impl crate::Listener for Count {
+            type Store = TestState;
+
+            fn on_change(&self, _cx: &Context, _state: Rc<Self::Store>) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        cx.init_listener(|| Count(calls.clone()));
+
+        cx.remove::<TestState>();
+        cx.set(TestState(1));
+        assert_eq!(calls.get(), 0);
+
+        // Listeners may be initialized again.
+        cx.init_listener(|| Count(calls.clone()));
+        cx.set(TestState(2));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn remove_cancels_pending_notification() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        cx.batch(|cx| {
+            cx.set(TestState(1));
+            cx.remove::<TestState>();
+        });
+
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn child_shares_parent_stores() {
+        let parent = Context::new();
+        let child = parent.child();
+
+        child.set(TestState(1));
+        assert_eq!(parent.get::<TestState>().0, 1);
+
+        parent.set(TestState(2));
+        assert_eq!(child.get::<TestState>().0, 2);
+    }
+
+    #[test]
+    fn child_subscribers_see_parent_changes() {
+        let parent = Context::new();
+        let child = parent.child();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            child.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        parent.set(TestState(1));
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn provided_store_is_local_to_child() {
+        let parent = Context::new();
+        let child = parent.child();
+        let grandchild = child.child();
+        child.provide(TestState(1));
+
+        grandchild.set(TestState(2));
+        parent.set(TestState(3));
+
+        assert_eq!(child.get::<TestState>().0, 2);
+        assert_eq!(parent.get::<TestState>().0, 3);
+    }
+
+    #[test]
+    fn provided_store_is_local_after_remove() {
+        let parent = Context::new();
+        let child = parent.child();
+        child.provide(TestState(1));
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            child.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        child.remove::<TestState>();
+        parent.set(TestState(2));
+        assert_eq!(child.get::<TestState>().0, 0);
+        assert_eq!(calls.get(), 0);
+
+        child.set(TestState(3));
+        assert_eq!(parent.get::<TestState>().0, 2);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn provide_sets_local_store() {
+        let cx = Context::new().child();
+        let calls = Rc::new(Cell::new(0));
+        cx.provide(TestState(1));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        cx.provide(TestState(2));
+
+        assert_eq!(cx.get::<TestState>().0, 2);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn child_batch_covers_parent_stores() {
+        let parent = Context::new();
+        let child = parent.child();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            parent.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        child.batch(|cx| {
+            cx.set(TestState(1));
+            cx.set(TestState(2));
+        });
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn stores_are_initialized_by_owner() {
+        let parent = Context::new();
+        let child = parent.child();
+
+        child.get::<TestState2>();
+
+        // `TestState2::new` initializes `TestState` with the context it's given.
+        assert!(parent.store_info::<TestState>().is_some());
+        assert!(child.stores().next().is_none());
+    }
+
+    /// Subscribes twice to `TestState`. The first subscriber sets it to 2 when it's 1. Returns
+    /// what each subscriber received, in order.
+    fn nested_reduce_order(mode: DispatchMode) -> Vec<(&'static str, u32)> {
+        let cx = Context::new();
+        cx.set_dispatch_mode(mode);
+        let seen = Mrc::new(Vec::new());
+        let _first = {
+            let (cx, seen) = (cx.clone(), seen.clone());
+            cx.clone().subscribe_silent(move |state: Rc<TestState>| {
+                seen.borrow_mut().push(("first", state.0));
+                if state.0 == 1 {
+                    cx.set(TestState(2));
+                }
+            })
+        };
+        let _second = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState>| {
+                seen.borrow_mut().push(("second", state.0))
+            })
+        };
+
+        cx.set(TestState(1));
+
+        let seen = seen.borrow().clone();
+        seen
+    }
+
+    #[test]
+    fn immediate_mode_notifies_nested_changes_first() {
+        assert_eq!(
+            nested_reduce_order(DispatchMode::Immediate),
+            vec![("first", 1), ("first", 2), ("second", 2), ("second", 1)]
+        );
+    }
+
+    #[test]
+    fn queued_mode_notifies_in_order() {
+        assert_eq!(
+            nested_reduce_order(DispatchMode::Queued),
+            vec![("first", 1), ("second", 1), ("first", 2), ("second", 2)]
+        );
+    }
+
+    #[test]
+    fn queued_mode_orders_changes_across_stores() {
+        let cx = Context::new();
+        cx.set_dispatch_mode(DispatchMode::Queued);
+        let seen = Mrc::new(Vec::new());
+        let _first = {
+            let (cx, seen) = (cx.clone(), seen.clone());
+            cx.clone().subscribe_silent(move |state: Rc<TestState>| {
+                seen.borrow_mut().push(format!("first {}", state.0));
+                cx.set(TestState2(state.0));
+            })
+        };
+        let _other = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState2>| {
+                seen.borrow_mut().push(format!("other {}", state.0))
+            })
+        };
+        let _second = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState>| {
+                seen.borrow_mut().push(format!("second {}", state.0))
+            })
+        };
+
+        cx.set(TestState(1));
+
+        assert_eq!(*seen.borrow(), vec!["first 1", "second 1", "other 1"]);
+    }
+
+    #[test]
+    fn queued_mode_applies_to_batches() {
+        let cx = Context::new();
+        cx.set_dispatch_mode(DispatchMode::Queued);
+        let seen = Mrc::new(Vec::new());
+        let _first = {
+            let cx = cx.clone();
+            cx.clone().subscribe_silent(move |_: Rc<TestState>| {
+                cx.set(TestState2(1));
+                cx.set(TestState2(2));
+            })
+        };
+        let _second = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState>| {
+                seen.borrow_mut().push(format!("second {}", state.0))
+            })
+        };
+        let _other = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState2>| {
+                seen.borrow_mut().push(format!("other {}", state.0))
+            })
+        };
+
+        cx.batch(|cx| cx.set(TestState(1)));
+
+        // Every state is delivered, once the batch's round completes.
+        assert_eq!(*seen.borrow(), vec!["second 1", "other 1", "other 2"]);
+    }
+
+    #[test]
+    fn dispatch_mode_is_shared_with_children() {
+        let cx = Context::new();
+        let child = cx.child();
+
+        child.set_dispatch_mode(DispatchMode::Queued);
+
+        assert_eq!(cx.dispatch_mode(), DispatchMode::Queued);
+        assert_eq!(cx.child().dispatch_mode(), DispatchMode::Queued);
+    }
 }
@@ -5,14 +5,307 @@ use crate::context;
 #[derive(PartialEq, Clone, Properties)]
 pub struct Props {
     pub children: Children,
+    /// Context to provide, instead of creating a new one. Children are recreated when it changes,
+    /// so they don't keep state or subscriptions from the previous context. State embedded in the
+    /// page by the server is loaded into it on the first render.
+    #[prop_or_default]
+    pub context: Option<context::Context>,
+    /// Called with a new context before children render, to set up its stores. It runs
+    /// synchronously during the first render, so it shouldn't do expensive work. Not called when
+    /// `context` is given.
+    #[prop_or_default]
+    pub init: Option<Callback<context::Context>>,
 }
 
+/// Provides a context for all stores used by its children.
+///
+/// ```
+/// use yew::prelude::*;
+/// use yewdux::prelude::*;
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Config {
+///     api_url: String,
+/// }
+///
+/// #[function_component]
+/// fn ApiUrl() -> Html {
+///     let config = use_store_value::<Config>();
+///     html! { <p>{ &config.api_url }</p> }
+/// }
+///
+/// #[function_component]
+/// fn App() -> Html {
+///     let init = Callback::from(|cx: yewdux::Context| {
+///         cx.set(Config {
+///             api_url: "https://example.com".to_string(),
+///         })
+///     });
+///
+///     html! {
+///         <YewduxRoot {init}>
+///             // Sees the config on its first render.
+///             <ApiUrl />
+///         </YewduxRoot>
+///     }
+/// }
+/// ```
 #[function_component]
-pub fn YewduxRoot(Props { children }: &Props) -> Html {
-    let ctx = use_state(context::Context::new);
+pub fn YewduxRoot(
+    Props {
+        children,
+        context,
+        init,
+    }: &Props,
+) -> Html {
+    let current = use_mut_ref(|| None::<Provided>);
+    let (ctx, generation) = {
+        let mut current = current.borrow_mut();
+        if let Some(generation) = next_generation(current.as_ref(), context.as_ref()) {
+            // State from the server is loaded on the first render, and into every context created
+            // here.
+            let payload = (generation == 0 || context.is_none())
+                .then(page_payload)
+                .flatten();
+            let ctx = prepare_context(context.as_ref(), init.as_ref(), payload.as_deref());
+            *current = Some(Provided {
+                given: context.clone(),
+                context: ctx,
+                generation,
+            });
+        }
+
+        let current = current.as_ref().expect("context initialized");
+        (current.context.clone(), current.generation)
+    };
+
+    html! {
+        <ContextProvider<context::Context> key={generation} context={ctx}>
+            { children.clone() }
+        </ContextProvider<context::Context>>
+    }
+}
+
+/// The context provided by a [YewduxRoot].
+struct Provided {
+    /// The `context` prop it was provided for.
+    given: Option<context::Context>,
+    context: context::Context,
+    /// How many contexts were provided before it.
+    generation: u32,
+}
+
+/// Generation of a new context to provide when `given` is the `context` prop, or `None` to keep
+/// the current one. A new context is provided on the first render, and whenever a different
+/// context is given.
+fn next_generation(current: Option<&Provided>, given: Option<&context::Context>) -> Option<u32> {
+    let Some(current) = current else {
+        return Some(0);
+    };
+
+    let changed = match (&current.given, given) {
+        (Some(a), Some(b)) => !a.ptr_eq(b),
+        (None, None) => false,
+        _ => true,
+    };
+
+    changed.then_some(current.generation + 1)
+}
+
+/// The context to provide for the `context` prop `given`, with server state loaded from `payload`.
+/// A new context is created when none is given, and set up with `init`.
+fn prepare_context(
+    given: Option<&context::Context>,
+    init: Option<&Callback<context::Context>>,
+    payload: Option<&str>,
+) -> context::Context {
+    let ctx = given.cloned().unwrap_or_default();
+    if let Some(payload) = payload {
+        if let Err(err) = crate::hydrate::load(&ctx, payload) {
+            crate::log::error!("Unable to hydrate state: {:?}", err);
+        }
+    }
+
+    if given.is_none() {
+        if let Some(init) = init {
+            init.emit(ctx.clone());
+        }
+    }
+
+    ctx
+}
+
+/// State embedded in the page by the server, if there is any.
+fn page_payload() -> Option<String> {
+    #[cfg(target_arch = "wasm32")]
+    match crate::hydrate::page_payload() {
+        Ok(payload) => return Some(payload),
+        Err(crate::hydrate::HydrateError::PayloadNotFound) => {}
+        Err(err) => crate::log::error!("Unable to hydrate state: {:?}", err),
+    }
+
+    None
+}
+
+#[derive(PartialEq, Clone, Properties)]
+pub struct ScopeProps {
+    pub children: Children,
+    /// Called once with the new context, to [provide](context::Context::provide) stores local to
+    /// this scope.
+    #[prop_or_default]
+    pub init: Option<Callback<context::Context>>,
+}
+
+/// Provides a [child](context::Context::child) of the surrounding context. Stores provided in
+/// `init` are local to this scope, and all others are shared with the surrounding context.
+///
+/// ```
+/// use yew::prelude::*;
+/// use yewdux::prelude::*;
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Theme(&'static str);
+///
+/// #[function_component]
+/// fn ThemeName() -> Html {
+///     let theme = use_store_value::<Theme>();
+///     html! { <p>{ theme.0 }</p> }
+/// }
+///
+/// #[function_component]
+/// fn Preview() -> Html {
+///     let init = Callback::from(|cx: yewdux::Context| cx.provide(Theme("dark")));
+///     // Components in this scope see the dark theme, and share every other store.
+///     html! {
+///         <YewduxScope {init}>
+///             <ThemeName />
+///         </YewduxScope>
+///     }
+/// }
+/// ```
+#[function_component]
+pub fn YewduxScope(ScopeProps { children, init }: &ScopeProps) -> Html {
+    let parent = use_context::<context::Context>();
+    let ctx = {
+        let init = init.clone();
+        use_state(move || {
+            let ctx = match parent {
+                Some(parent) => parent.child(),
+                #[cfg(target_arch = "wasm32")]
+                None => context::Context::global().child(),
+                #[cfg(not(target_arch = "wasm32"))]
+                None => context::Context::new(),
+            };
+
+            if let Some(init) = init {
+                init.emit(ctx.clone());
+            }
+
+            ctx
+        })
+    };
+
     html! {
         <ContextProvider<context::Context> context={(*ctx).clone()}>
             { children.clone() }
         </ContextProvider<context::Context>>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::store::Store;
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    struct Posts(Vec<String>);
+    impl Store for Posts {
+        fn new(cx: &context::Context) -> Self {
+            crate::hydrate::init(cx).unwrap_or_else(|| Self(Vec::new()))
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    fn server_payload() -> String {
+        let server = context::Context::new();
+        server.set(Posts(vec!["Hello".to_string()]));
+        crate::hydrate::serialize(&server).unwrap()
+    }
+
+    #[test]
+    fn given_context_is_hydrated() {
+        let given = context::Context::new();
+        let init = Callback::from(|_: context::Context| panic!("not called for a given context"));
+
+        let ctx = prepare_context(Some(&given), Some(&init), Some(&server_payload()));
+
+        assert!(ctx.ptr_eq(&given));
+        assert_eq!(given.get::<Posts>().0, vec!["Hello".to_string()]);
+    }
+
+    #[test]
+    fn created_context_is_hydrated_before_init() {
+        let init = Callback::from(|cx: context::Context| {
+            assert_eq!(cx.get::<Posts>().0, vec!["Hello".to_string()]);
+            cx.reduce_mut(|posts: &mut Posts| posts.0.push("World".to_string()));
+        });
+
+        let ctx = prepare_context(None, Some(&init), Some(&server_payload()));
+
+        assert_eq!(ctx.get::<Posts>().0.len(), 2);
+    }
+
+    fn provided(given: Option<&context::Context>, generation: u32) -> Provided {
+        Provided {
+            given: given.cloned(),
+            context: given.cloned().unwrap_or_default(),
+            generation,
+        }
+    }
+
+    #[test]
+    fn first_render_provides_context() {
+        assert_eq!(next_generation(None, None), Some(0));
+        assert_eq!(
+            next_generation(None, Some(&context::Context::new())),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn same_given_context_is_kept() {
+        let cx = context::Context::new();
+        let current = provided(Some(&cx), 0);
+
+        assert_eq!(next_generation(Some(&current), Some(&cx.clone())), None);
+    }
+
+    #[test]
+    fn different_given_context_is_provided() {
+        let current = provided(Some(&context::Context::new()), 0);
+
+        assert_eq!(
+            next_generation(Some(&current), Some(&context::Context::new())),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn removed_context_is_replaced() {
+        let current = provided(Some(&context::Context::new()), 2);
+
+        assert_eq!(next_generation(Some(&current), None), Some(3));
+    }
+
+    #[test]
+    fn created_context_is_kept() {
+        let current = provided(None, 0);
+
+        assert_eq!(next_generation(Some(&current), None), None);
+    }
+}
@@ -4,13 +4,21 @@
 //! This module enables the creation of stores that are computed from other stores,
 //! allowing for automatic synchronization when the source stores change.
 //!
-//! There are two approaches available:
+//! There are three approaches available:
 //! - `DerivedFrom`: For immutable transformations where a new derived store is created on each update
 //! - `DerivedFromMut`: For mutable transformations where the derived store is updated in-place
+//! - `DerivedFromMany`: For immutable transformations of several source stores at once
+//!
+//! When a store changes, every store derived from it, directly or not, is recomputed once in
+//! dependency order before any subscriber is notified. A derived store is only recomputed when one
+//! of its sources changed. Deriving a store from itself, directly or not, panics.
 
-use std::rc::Rc;
+use std::{any::TypeId, rc::Rc};
 
-use crate::Context;
+use crate::{
+    graph::{self, Source},
+    Context,
+};
 
 /// Trait for creating a derived store that transforms from another store immutably.
 ///
@@ -33,36 +41,10 @@ pub trait DerivedFrom<Store: crate::Store>: crate::Store + 'static {
     fn on_change(&self, state: Rc<Store>) -> Self;
 }
 
-/// Internal listener that updates the derived store when the source store changes.
-///
-/// This struct implements the `Listener` trait for the source store and manages
-/// updating the derived store through its `Dispatch`.
-struct Listener<Store, Derived>
-where
-    Store: crate::Store,
-    Derived: DerivedFrom<Store>,
-{
-    derived: crate::Dispatch<Derived>,
-    _marker: std::marker::PhantomData<Store>,
-}
-
-impl<Store, Derived> crate::Listener for Listener<Store, Derived>
-where
-    Store: crate::Store,
-    Derived: DerivedFrom<Store>,
-{
-    type Store = Store;
-
-    fn on_change(&self, _cx: &Context, state: Rc<Self::Store>) {
-        self.derived
-            .reduce(|derived| derived.on_change(Rc::clone(&state)).into());
-    }
-}
-
 /// Initializes a derived store that automatically updates when the source store changes.
 ///
-/// This function sets up a listener on the source store that will update the derived store
-/// whenever the source store changes, using the `DerivedFrom` implementation to transform the state.
+/// This function registers the derived store with the context, which updates it whenever the
+/// source store changes, using the `DerivedFrom` implementation to transform the state.
 ///
 /// # Type Parameters
 ///
@@ -118,12 +100,20 @@ where
     Store: crate::Store,
     Derived: DerivedFrom<Store>,
 {
-    crate::init_listener(
-        || Listener {
-            derived: crate::Dispatch::<Derived>::new(cx),
-            _marker: std::marker::PhantomData,
-        },
+    fn recompute<Store, Derived>(cx: &Context) -> bool
+    where
+        Store: crate::Store,
+        Derived: DerivedFrom<Store>,
+    {
+        let state = cx.get::<Store>();
+        cx.reduce_silent(|derived: Rc<Derived>| derived.on_change(state).into())
+    }
+
+    graph::add::<Derived>(
         cx,
+        TypeId::of::<fn(Store) -> Derived>(),
+        &[Source::of::<Store>()],
+        recompute::<Store, Derived>,
     );
 }
 
@@ -144,36 +134,10 @@ pub trait DerivedFromMut<Store: crate::Store>: crate::Store + Clone + 'static {
     fn on_change(&mut self, state: Rc<Store>);
 }
 
-/// Internal listener that mutably updates the derived store when the source store changes.
-///
-/// This struct implements the `Listener` trait for the source store and manages
-/// updating the derived store through its `Dispatch` using mutable references.
-struct ListenerMut<Store, Derived>
-where
-    Store: crate::Store,
-    Derived: DerivedFromMut<Store>,
-{
-    derived: crate::Dispatch<Derived>,
-    _marker: std::marker::PhantomData<Store>,
-}
-
-impl<Store, Derived> crate::Listener for ListenerMut<Store, Derived>
-where
-    Store: crate::Store,
-    Derived: DerivedFromMut<Store>,
-{
-    type Store = Store;
-
-    fn on_change(&self, _cx: &Context, state: Rc<Self::Store>) {
-        self.derived
-            .reduce_mut(|derived| derived.on_change(Rc::clone(&state)));
-    }
-}
-
 /// Initializes a derived store that is mutably updated when the source store changes.
 ///
-/// This function sets up a listener on the source store that will update the derived store
-/// whenever the source store changes, using the `DerivedFromMut` implementation to transform the state.
+/// This function registers the derived store with the context, which updates it whenever the
+/// source store changes, using the `DerivedFromMut` implementation to transform the state.
 ///
 /// # Type Parameters
 ///
@@ -229,15 +193,472 @@ where
     Store: crate::Store,
     Derived: DerivedFromMut<Store>,
 {
-    crate::init_listener(
-        || ListenerMut {
-            derived: crate::Dispatch::<Derived>::new(cx),
-            _marker: std::marker::PhantomData,
-        },
+    fn recompute<Store, Derived>(cx: &Context) -> bool
+    where
+        Store: crate::Store,
+        Derived: DerivedFromMut<Store>,
+    {
+        let state = cx.get::<Store>();
+        cx.reduce_silent(|mut derived: Rc<Derived>| {
+            Rc::make_mut(&mut derived).on_change(state);
+            derived
+        })
+    }
+
+    graph::add::<Derived>(
+        cx,
+        TypeId::of::<fn(&mut Derived, Store)>(),
+        &[Source::of::<Store>()],
+        recompute::<Store, Derived>,
+    );
+}
+
+/// Source stores of a [DerivedFromMany] store: a tuple of two to six stores.
+pub trait Sources: 'static {
+    /// Current state of every source, like `(Rc<A>, Rc<B>)`.
+    type State;
+
+    /// Get the current state of every source.
+    fn get(cx: &Context) -> Self::State;
+
+    /// Every source store.
+    #[doc(hidden)]
+    fn sources() -> Vec<Source>;
+}
+
+/// Trait for creating a derived store that transforms from several stores immutably.
+///
+/// When any of the source stores changes, `on_change` is called once with the current state of
+/// all of them.
+///
+/// With the `Store` macro, it's registered with `#[store(derived_from_many(A, B))]`.
+///
+/// # Type Parameters
+///
+/// * `S`: The source stores this store derives from, as a tuple like `(A, B)`
+///
+/// # Example
+///
+/// ```rust
+/// use std::rc::Rc;
+/// use yewdux::prelude::*;
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Price(u32);
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Quantity(u32);
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// #[store(derived_from_many(Price, Quantity))]
+/// struct Total(u32);
+///
+/// impl DerivedFromMany<(Price, Quantity)> for Total {
+///     fn on_change(&self, (price, quantity): (Rc<Price>, Rc<Quantity>)) -> Self {
+///         Self(price.0 * quantity.0)
+///     }
+/// }
+///
+/// let cx = yewdux::Context::new();
+/// // Sources are listened to once the derived store is initialized.
+/// let total = Dispatch::<Total>::new(&cx);
+/// assert_eq!(total.get().0, 0);
+///
+/// cx.set(Price(3));
+/// cx.set(Quantity(2));
+/// assert_eq!(total.get().0, 6);
+/// ```
+pub trait DerivedFromMany<S: Sources>: crate::Store + 'static {
+    /// Creates a new instance of the derived store based on the current state of the source
+    /// stores.
+    ///
+    /// # Parameters
+    ///
+    /// * `state`: The current state of every source store, in the order of `S`
+    ///
+    /// # Returns
+    ///
+    /// A new instance of the derived store
+    fn on_change(&self, state: S::State) -> Self;
+}
+
+macro_rules! impl_sources {
+    ($($store:ident),+) => {
+        impl<$($store: crate::Store),+> Sources for ($($store,)+) {
+            type State = ($(Rc<$store>,)+);
+
+            fn get(cx: &Context) -> Self::State {
+                ($(cx.get::<$store>(),)+)
+            }
+
+            fn sources() -> Vec<Source> {
+                vec![$(Source::of::<$store>(),)+]
+            }
+        }
+    };
+}
+
+impl_sources!(A, B);
+impl_sources!(A, B, C);
+impl_sources!(A, B, C, D);
+impl_sources!(A, B, C, D, E);
+impl_sources!(A, B, C, D, E, F);
+
+/// Initializes a derived store that automatically updates when any of its source stores change.
+///
+/// # Type Parameters
+///
+/// * `S`: The source stores to derive from, as a tuple like `(A, B)`
+/// * `Derived`: The derived store type that implements `DerivedFromMany<S>`
+///
+/// # Parameters
+///
+/// * `cx`: The Yewdux context
+///
+/// # Example
+///
+/// ```rust
+/// use std::rc::Rc;
+/// use yewdux::prelude::*;
+/// use yewdux::derived_from::derive_from_many;
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Todos(Vec<(String, bool)>);
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct ShowDone(bool);
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Visible(Vec<String>);
+///
+/// impl DerivedFromMany<(Todos, ShowDone)> for Visible {
+///     fn on_change(&self, (todos, show_done): (Rc<Todos>, Rc<ShowDone>)) -> Self {
+///         let visible = todos
+///             .0
+///             .iter()
+///             .filter(|(_, done)| show_done.0 || !done)
+///             .map(|(name, _)| name.clone())
+///             .collect();
+///
+///         Self(visible)
+///     }
+/// }
+///
+/// let cx = yewdux::Context::new();
+/// derive_from_many::<(Todos, ShowDone), Visible>(&cx);
+///
+/// cx.set(Todos(vec![("Write".into(), true), ("Review".into(), false)]));
+/// assert_eq!(cx.get::<Visible>().0, vec!["Review".to_string()]);
+///
+/// cx.set(ShowDone(true));
+/// assert_eq!(cx.get::<Visible>().0.len(), 2);
+/// ```
+pub fn derive_from_many<S, Derived>(cx: &Context)
+where
+    S: Sources,
+    Derived: DerivedFromMany<S>,
+{
+    fn recompute<S, Derived>(cx: &Context) -> bool
+    where
+        S: Sources,
+        Derived: DerivedFromMany<S>,
+    {
+        let state = S::get(cx);
+        cx.reduce_silent(|derived: Rc<Derived>| derived.on_change(state).into())
+    }
+
+    graph::add::<Derived>(
         cx,
+        TypeId::of::<fn(S) -> Derived>(),
+        &S::sources(),
+        recompute::<S, Derived>,
     );
 }
 
+#[cfg(feature = "future")]
+pub use self::future::{AsyncDerived, AsyncStatus, DerivedFromAsync};
+
+#[cfg(feature = "future")]
+mod future {
+    use std::{future::Future, marker::PhantomData};
+
+    use super::*;
+    use crate::subscriber::SubscriberId;
+
+    /// Trait for a value derived asynchronously from another store.
+    ///
+    /// It is held by an [AsyncDerived] store, which runs `on_change` whenever the source store
+    /// changes. Results of outdated runs are discarded, so only the latest state is applied.
+    ///
+    /// # Type Parameters
+    ///
+    /// * `Store`: The source store type this value derives from
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::rc::Rc;
+    /// use yewdux::prelude::*;
+    /// use yew::prelude::*;
+    /// use yewdux::derived_from::{AsyncDerived, DerivedFromAsync};
+    ///
+    /// #[derive(Default, Clone, PartialEq, Store)]
+    /// struct Document(String);
+    ///
+    /// struct WordCount(usize);
+    ///
+    /// impl DerivedFromAsync<Document> for WordCount {
+    ///     type Error = ();
+    ///
+    ///     async fn on_change(state: Rc<Document>) -> Result<Self, Self::Error> {
+    ///         // Do expensive work here, like in a worker.
+    ///         Ok(Self(state.0.split_whitespace().count()))
+    ///     }
+    /// }
+    ///
+    /// #[function_component]
+    /// fn Words() -> Html {
+    ///     let words = use_store_value::<AsyncDerived<Document, WordCount>>();
+    ///     match words.value() {
+    ///         Some(count) if !words.is_loading() => html! { { count.0 } },
+    ///         _ => html! { "Counting..." },
+    ///     }
+    /// }
+    /// ```
+    pub trait DerivedFromAsync<Store: crate::Store>: Sized + 'static {
+        type Error: 'static;
+
+        /// Computes the value from the current state of the source store.
+        fn on_change(state: Rc<Store>) -> impl Future<Output = Result<Self, Self::Error>>;
+    }
+
+    /// Status of an [AsyncDerived] store.
+    #[derive(Debug)]
+    pub enum AsyncStatus<E> {
+        /// Computing a value for the latest state of the source store.
+        Loading,
+        /// The value is up to date with the source store.
+        Ready,
+        /// Computing a value for the latest state of the source store failed.
+        Error(Rc<E>),
+    }
+
+    impl<E> Clone for AsyncStatus<E> {
+        fn clone(&self) -> Self {
+            match self {
+                Self::Loading => Self::Loading,
+                Self::Ready => Self::Ready,
+                Self::Error(err) => Self::Error(Rc::clone(err)),
+            }
+        }
+    }
+
+    impl<E> PartialEq for AsyncStatus<E> {
+        fn eq(&self, other: &Self) -> bool {
+            match (self, other) {
+                (Self::Loading, Self::Loading) | (Self::Ready, Self::Ready) => true,
+                (Self::Error(a), Self::Error(b)) => Rc::ptr_eq(a, b),
+                _ => false,
+            }
+        }
+    }
+
+    /// A store holding `Derived`, computed asynchronously from `Store` with [DerivedFromAsync].
+    ///
+    /// Computing starts once this store has a subscriber, like a component using it, and again
+    /// whenever `Store` changes while it does. Until then it stays [loading](AsyncStatus::Loading),
+    /// so it may be read without an async runtime, like during server rendering or in tests.
+    ///
+    /// Unlike other derived stores, it's updated by a subscription to `Store` rather than before
+    /// anyone is notified. So when `Store` changes, its subscribers, and stores derived from both
+    /// it and this store, may briefly see this store before it switches to
+    /// [loading](AsyncStatus::Loading).
+    pub struct AsyncDerived<Store, Derived>
+    where
+        Store: crate::Store,
+        Derived: DerivedFromAsync<Store>,
+    {
+        status: AsyncStatus<Derived::Error>,
+        value: Option<Rc<Derived>>,
+        _marker: PhantomData<Store>,
+    }
+
+    impl<Store, Derived> AsyncDerived<Store, Derived>
+    where
+        Store: crate::Store,
+        Derived: DerivedFromAsync<Store>,
+    {
+        pub fn status(&self) -> &AsyncStatus<Derived::Error> {
+            &self.status
+        }
+
+        /// The latest computed value. Kept while a new one is loading, or if computing it failed.
+        pub fn value(&self) -> Option<&Derived> {
+            self.value.as_deref()
+        }
+
+        pub fn is_loading(&self) -> bool {
+            matches!(self.status, AsyncStatus::Loading)
+        }
+
+        /// Error from computing a value for the latest state of the source store, if any.
+        pub fn error(&self) -> Option<&Derived::Error> {
+            match &self.status {
+                AsyncStatus::Error(err) => Some(err),
+                _ => None,
+            }
+        }
+
+        fn with_status(&self, status: AsyncStatus<Derived::Error>) -> Self {
+            Self {
+                status,
+                value: self.value.clone(),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<Store, Derived> crate::Store for AsyncDerived<Store, Derived>
+    where
+        Store: crate::Store,
+        Derived: DerivedFromAsync<Store>,
+    {
+        fn new(cx: &Context) -> Self {
+            let subscribed = cx
+                .internal::<Computation<Store, Derived>>()
+                .borrow()
+                .source
+                .is_some();
+            // Subscribed from this context, so a source provided to it is the one derived from.
+            let source = (!subscribed).then(|| {
+                let weak = cx.downgrade();
+                cx.subscribe_passive(move |state: Rc<Store>| {
+                    if let Some(cx) = weak.upgrade() {
+                        on_source_change::<Store, Derived>(&cx, state);
+                    }
+                })
+            });
+            cx.internal::<Computation<Store, Derived>>()
+                .with_mut(|computation| {
+                    computation.stale = true;
+                    if source.is_some() {
+                        computation.source = source;
+                    }
+                });
+
+            Self {
+                status: AsyncStatus::Loading,
+                value: None,
+                _marker: PhantomData,
+            }
+        }
+
+        fn should_notify(&self, old: &Self) -> bool {
+            let same_value = match (&self.value, &old.value) {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            };
+
+            self.status != old.status || !same_value
+        }
+
+        fn on_active(&self, cx: &Context) {
+            let stale = cx.internal::<Computation<Store, Derived>>().borrow().stale;
+            if stale {
+                start::<Store, Derived>(cx, cx.get::<Store>());
+            }
+        }
+    }
+
+    /// Computing of `Derived`, in the context that owns its [AsyncDerived] store.
+    struct Computation<Store: crate::Store, Derived> {
+        /// Number of times computing started, to discard outdated results.
+        generation: u64,
+        /// Whether `Store` changed since computing last started.
+        stale: bool,
+        source: Option<SubscriberId<Store>>,
+        _marker: PhantomData<Derived>,
+    }
+
+    impl<Store: crate::Store, Derived> Default for Computation<Store, Derived> {
+        fn default() -> Self {
+            Self {
+                generation: 0,
+                stale: false,
+                source: None,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    /// Start computing `Derived` from `state`.
+    fn start<Store, Derived>(cx: &Context, state: Rc<Store>)
+    where
+        Store: crate::Store,
+        Derived: DerivedFromAsync<Store>,
+    {
+        let cx = cx.owner::<AsyncDerived<Store, Derived>>();
+        let generation = cx
+            .internal::<Computation<Store, Derived>>()
+            .with_mut(|computation| {
+                computation.stale = false;
+                computation.generation += 1;
+                computation.generation
+            });
+
+        let future = Derived::on_change(state);
+        let cx = cx.downgrade();
+        yew::platform::spawn_local(async move {
+            let result = future.await;
+            let Some(cx) = cx.upgrade() else {
+                return;
+            };
+
+            // Outdated, because the source store changed since.
+            if cx
+                .internal::<Computation<Store, Derived>>()
+                .borrow()
+                .generation
+                != generation
+            {
+                return;
+            }
+
+            cx.reduce(|derived: Rc<AsyncDerived<Store, Derived>>| {
+                match result {
+                    Ok(value) => AsyncDerived {
+                        status: AsyncStatus::Ready,
+                        value: Some(Rc::new(value)),
+                        _marker: PhantomData,
+                    },
+                    Err(err) => derived.with_status(AsyncStatus::Error(Rc::new(err))),
+                }
+                .into()
+            });
+        });
+    }
+
+    /// Start computing again when the source store changes, or once the derived store is active.
+    fn on_source_change<Store, Derived>(cx: &Context, state: Rc<Store>)
+    where
+        Store: crate::Store,
+        Derived: DerivedFromAsync<Store>,
+    {
+        cx.reduce(|derived: Rc<AsyncDerived<Store, Derived>>| {
+            derived.with_status(AsyncStatus::Loading).into()
+        });
+
+        if cx.subscriber_count::<AsyncDerived<Store, Derived>>() > 0 {
+            start::<Store, Derived>(cx, state);
+        } else {
+            cx.owner::<AsyncDerived<Store, Derived>>()
+                .internal::<Computation<Store, Derived>>()
+                .with_mut(|computation| computation.stale = true);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Dispatch;
@@ -327,4 +748,242 @@ mod tests {
         dispatch_state.reduce_mut(|state| state.0 += 1);
         assert_eq!(dispatch_derived.get().0, 1);
     }
+
+    #[test]
+    fn can_derive_from_many() {
+        #[derive(Clone, PartialEq, Eq)]
+        struct TestState(u32);
+        impl crate::Store for TestState {
+            fn new(_cx: &crate::Context) -> Self {
+                Self(0)
+            }
+
+            fn should_notify(&self, other: &Self) -> bool {
+                self != other
+            }
+        }
+
+        #[derive(Clone, PartialEq, Eq)]
+        struct TestState2(u32);
+        impl crate::Store for TestState2 {
+            fn new(_cx: &crate::Context) -> Self {
+                Self(0)
+            }
+
+            fn should_notify(&self, other: &Self) -> bool {
+                self != other
+            }
+        }
+
+        #[derive(Clone, PartialEq, Eq)]
+        struct TestDerived {
+            sum: u32,
+            computed: u32,
+        }
+        impl crate::Store for TestDerived {
+            fn new(_cx: &crate::Context) -> Self {
+                Self {
+                    sum: 0,
+                    computed: 0,
+                }
+            }
+
+            fn should_notify(&self, other: &Self) -> bool {
+                self != other
+            }
+        }
+
+        impl DerivedFromMany<(TestState, TestState2)> for TestDerived {
+            fn on_change(&self, (a, b): (Rc<TestState>, Rc<TestState2>)) -> Self {
+                Self {
+                    sum: a.0 + b.0,
+                    computed: self.computed + 1,
+                }
+            }
+        }
+
+        let cx = crate::Context::new();
+        cx.derived_from_many::<(TestState, TestState2), TestDerived>();
+
+        let dispatch_derived = Dispatch::<TestDerived>::new(&cx);
+
+        Dispatch::<TestState>::new(&cx).set(TestState(1));
+        assert_eq!(dispatch_derived.get().sum, 1);
+        assert_eq!(dispatch_derived.get().computed, 1);
+
+        Dispatch::<TestState2>::new(&cx).set(TestState2(2));
+        assert_eq!(dispatch_derived.get().sum, 3);
+        assert_eq!(dispatch_derived.get().computed, 2);
+    }
+
+    #[cfg(feature = "future")]
+    mod future {
+        use std::cell::RefCell;
+
+        use yew::platform::pinned::oneshot;
+
+        use super::*;
+        use crate::future::run;
+
+        type Response = Result<u32, &'static str>;
+
+        /// Source state, holding the channel its derived value is received from.
+        #[derive(Clone)]
+        struct Request(Rc<RefCell<Option<oneshot::Receiver<Response>>>>);
+        impl crate::Store for Request {
+            fn new(_cx: &crate::Context) -> Self {
+                Self(Default::default())
+            }
+
+            fn should_notify(&self, other: &Self) -> bool {
+                !Rc::ptr_eq(&self.0, &other.0)
+            }
+        }
+
+        impl Request {
+            fn new() -> (Self, oneshot::Sender<Response>) {
+                let (tx, rx) = oneshot::channel();
+                (Self(Rc::new(RefCell::new(Some(rx)))), tx)
+            }
+        }
+
+        /// The response to a request.
+        #[derive(Debug)]
+        struct Received(u32);
+        impl DerivedFromAsync<Request> for Received {
+            type Error = &'static str;
+
+            async fn on_change(state: Rc<Request>) -> Result<Self, Self::Error> {
+                let rx = state.0.borrow_mut().take().ok_or("no request")?;
+                rx.await.map_err(|_| "no response")?.map(Self)
+            }
+        }
+
+        type Derived = AsyncDerived<Request, Received>;
+
+        /// Resolves to the next state of `Derived`.
+        async fn next_change(cx: &crate::Context) -> Rc<Derived> {
+            let (tx, rx) = oneshot::channel();
+            let tx = RefCell::new(Some(tx));
+            let _dispatch = Dispatch::<Derived>::new(cx).subscribe_silent(move |state| {
+                if let Some(tx) = tx.borrow_mut().take() {
+                    let _ = tx.send(state);
+                }
+            });
+
+            rx.await.unwrap()
+        }
+
+        /// Lets every task that is ready run.
+        async fn settle() {
+            let (tx, rx) = oneshot::channel();
+            yew::platform::spawn_local(async move {
+                let _ = tx.send(());
+            });
+
+            rx.await.unwrap();
+        }
+
+        #[test]
+        fn loads_then_becomes_ready() {
+            run(|| async {
+                let cx = crate::Context::new();
+                let (request, tx) = Request::new();
+                cx.set(request);
+                let dispatch = Dispatch::<Derived>::new(&cx);
+                assert!(dispatch.get().is_loading());
+                assert!(dispatch.get().value().is_none());
+
+                tx.send(Ok(1)).unwrap();
+                let state = next_change(&cx).await;
+                assert_eq!(state.status(), &AsyncStatus::Ready);
+                assert_eq!(state.value().unwrap().0, 1);
+
+                let (request, tx) = Request::new();
+                cx.set(request);
+                // Previous value is kept while loading.
+                assert!(dispatch.get().is_loading());
+                assert_eq!(dispatch.get().value().unwrap().0, 1);
+
+                tx.send(Ok(2)).unwrap();
+                assert_eq!(next_change(&cx).await.value().unwrap().0, 2);
+            });
+        }
+
+        #[test]
+        fn outdated_results_are_discarded() {
+            run(|| async {
+                let cx = crate::Context::new();
+                let (request, first) = Request::new();
+                cx.set(request);
+                // Starts computing once subscribed to.
+                let dispatch = Dispatch::<Derived>::new(&cx).subscribe_silent(|_| ());
+                assert!(dispatch.get().is_loading());
+
+                let (request, second) = Request::new();
+                cx.set(request);
+                second.send(Ok(2)).unwrap();
+                assert_eq!(next_change(&cx).await.value().unwrap().0, 2);
+
+                // The first request resolves now, but is outdated.
+                first.send(Ok(1)).unwrap();
+                settle().await;
+                assert_eq!(dispatch.get().value().unwrap().0, 2);
+                assert!(!dispatch.get().is_loading());
+            });
+        }
+
+        #[test]
+        fn failure_sets_error_status() {
+            run(|| async {
+                let cx = crate::Context::new();
+                let (request, tx) = Request::new();
+                cx.set(request);
+                let dispatch = Dispatch::<Derived>::new(&cx);
+                assert!(dispatch.get().is_loading());
+                tx.send(Ok(1)).unwrap();
+                next_change(&cx).await;
+
+                let (request, tx) = Request::new();
+                cx.set(request);
+                tx.send(Err("unlucky")).unwrap();
+                next_change(&cx).await;
+
+                assert_eq!(dispatch.get().error(), Some(&"unlucky"));
+                assert_eq!(dispatch.get().value().unwrap().0, 1);
+            });
+        }
+
+        #[test]
+        fn does_not_start_without_subscribers() {
+            // Not in a runtime, so starting would panic.
+            let cx = crate::Context::new();
+            cx.set(Request::new().0);
+            assert!(cx.get::<Derived>().is_loading());
+
+            cx.set(Request::new().0);
+            assert!(cx.get::<Derived>().is_loading());
+        }
+
+        #[test]
+        fn derives_from_source_provided_to_child() {
+            run(|| async {
+                let parent = crate::Context::new();
+                let child = parent.child();
+                child.set_constructor::<Derived>(<Derived as crate::Store>::new);
+                let (request, tx) = Request::new();
+                child.provide(request);
+                let _dispatch = Dispatch::<Derived>::new(&child).subscribe_silent(|_| ());
+
+                tx.send(Ok(1)).unwrap();
+                assert_eq!(next_change(&child).await.value().unwrap().0, 1);
+
+                let (request, tx) = Request::new();
+                child.set(request);
+                parent.set(Request::new().0);
+                tx.send(Ok(2)).unwrap();
+                assert_eq!(next_change(&child).await.value().unwrap().0, 2);
+            });
+        }
+    }
 }
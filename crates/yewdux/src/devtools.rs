@@ -0,0 +1,682 @@
+//! Bridge to the [Redux DevTools](https://github.com/reduxjs/redux-devtools) browser extension.
+//!
+//! Every change to a tracked store is sent to the extension as an action, along with a snapshot of
+//! all tracked stores. Time travel from the extension is applied with
+//! [Context::set](crate::Context::set).
+//!
+//! Messages are sent through a [Transport]. In the browser, [DevTools::connect] uses
+//! `window.postMessage`, which the extension listens to. Any other transport may be used with
+//! [DevTools::new], and incoming messages passed to [DevTools::receive].
+//!
+//! ```
+//! use yewdux::{devtools::DevTools, prelude::*};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Default, Clone, PartialEq, Store, Serialize, Deserialize)]
+//! struct Counter(u32);
+//!
+//! struct Console;
+//! impl yewdux::devtools::Transport for Console {
+//!     fn send(&self, message: serde_json::Value) {
+//!         println!("{}", message);
+//!     }
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! let devtools = DevTools::new(&cx, Console);
+//! devtools.track::<Counter>();
+//! devtools.init();
+//!
+//! cx.set(Counter(1));
+//! ```
+//!
+//! # Toggling actions
+//!
+//! Stores don't record the reducers that changed them, so skipping an action can't re-run the
+//! actions after it. Instead, each tracked store is set to its snapshot from the latest action
+//! that changed it and isn't skipped.
+use std::{
+    any::{type_name, Any},
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, HashMap},
+    rc::{Rc, Weak},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::{store::Store, Context};
+
+const PAGE_SOURCE: &str = "@devtools-page";
+const EXTENSION_SOURCE: &str = "@devtools-extension";
+const INIT_ACTION: &str = "@@INIT";
+
+/// Sends messages to the devtools.
+pub trait Transport: 'static {
+    fn send(&self, message: Value);
+}
+
+type Snapshot = Box<dyn Fn(&Context) -> Option<Value>>;
+type Restore = Box<dyn Fn(&Context, Value) -> Result<(), serde_json::Error>>;
+
+struct Tracked {
+    name: &'static str,
+    snapshot: Snapshot,
+    restore: Restore,
+    _subscription: Box<dyn Any>,
+}
+
+struct Step {
+    action: Value,
+    /// Snapshot of all tracked stores after this action.
+    state: Map<String, Value>,
+    /// Stores changed by this action.
+    changed: Vec<String>,
+}
+
+impl Step {
+    fn new(
+        action: Value,
+        state: Map<String, Value>,
+        previous: Option<&Map<String, Value>>,
+    ) -> Self {
+        let changed = state
+            .iter()
+            .filter(|(name, value)| previous.and_then(|x| x.get(*name)) != Some(value))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        Self {
+            action,
+            state,
+            changed,
+        }
+    }
+}
+
+struct Inner {
+    cx: Context,
+    name: String,
+    transport: Box<dyn Transport>,
+    stores: RefCell<Vec<Tracked>>,
+    /// First step is always the initial state.
+    history: RefCell<Vec<Step>>,
+    skipped: RefCell<BTreeSet<usize>>,
+    current: Cell<usize>,
+    /// Snapshots of stores set from the devtools, by name. Their change isn't sent back as a new
+    /// action, even when subscribers are notified later by a scheduler.
+    restored: RefCell<HashMap<&'static str, Value>>,
+}
+
+/// Connection between a [Context] and the Redux DevTools. See the [module docs](self).
+#[derive(Clone)]
+pub struct DevTools {
+    inner: Rc<Inner>,
+}
+
+impl std::fmt::Debug for DevTools {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DevTools")
+            .field("name", &self.inner.name)
+            .field("history", &self.inner.history.borrow().len())
+            .finish()
+    }
+}
+
+impl DevTools {
+    /// Create a bridge that sends messages through `transport`.
+    pub fn new<T: Transport>(cx: &Context, transport: T) -> Self {
+        Self {
+            inner: Rc::new(Inner {
+                cx: cx.clone(),
+                name: "yewdux".to_string(),
+                transport: Box::new(transport),
+                stores: Default::default(),
+                history: Default::default(),
+                skipped: Default::default(),
+                current: Default::default(),
+                restored: Default::default(),
+            }),
+        }
+    }
+
+    /// Connect to the browser extension with `window.postMessage`, tracking no stores yet.
+    #[cfg(target_arch = "wasm32")]
+    pub fn connect(cx: &Context) -> Result<Self, wasm_bindgen::JsValue> {
+        use wasm_bindgen::{prelude::Closure, JsCast};
+
+        let devtools = Self::new(cx, PostMessage);
+        let closure = {
+            let devtools = Rc::downgrade(&devtools.inner);
+            Closure::wrap(Box::new(move |event: web_sys::MessageEvent| {
+                let Some(inner) = devtools.upgrade() else {
+                    return;
+                };
+
+                let message = js_sys::JSON::stringify(&event.data())
+                    .ok()
+                    .and_then(|x| x.as_string())
+                    .and_then(|x| serde_json::from_str(&x).ok());
+                if let Some(message) = message {
+                    DevTools { inner }.receive(&message);
+                }
+            }) as Box<dyn FnMut(web_sys::MessageEvent)>)
+        };
+
+        web_sys::window()
+            .ok_or_else(|| wasm_bindgen::JsValue::from_str("Window not found"))?
+            .add_event_listener_with_callback("message", closure.as_ref().unchecked_ref())?;
+        closure.forget();
+
+        devtools.init();
+
+        Ok(devtools)
+    }
+
+    /// Send changes to `S` to the devtools, and allow time travel for it. Does nothing if `S` is
+    /// already tracked.
+    pub fn track<S>(&self)
+    where
+        S: Store + Serialize + DeserializeOwned,
+    {
+        let name = type_name::<S>();
+        if self.inner.stores.borrow().iter().any(|x| x.name == name) {
+            return;
+        }
+
+        let subscription = {
+            let devtools = Rc::downgrade(&self.inner);
+            self.inner.cx.subscribe_passive::<S, _>(move |_| {
+                if let Some(inner) = Weak::upgrade(&devtools) {
+                    DevTools { inner }.on_change(name);
+                }
+            })
+        };
+
+        self.inner.stores.borrow_mut().push(Tracked {
+            name,
+            snapshot: Box::new(|cx| match serde_json::to_value(cx.get::<S>().as_ref()) {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    crate::log::error!("Unable to serialize {}: {:?}", type_name::<S>(), err);
+                    None
+                }
+            }),
+            restore: Box::new(|cx, value| {
+                cx.set(serde_json::from_value::<S>(value)?);
+                Ok(())
+            }),
+            _subscription: Box::new(subscription),
+        });
+    }
+
+    /// Start a new session with current state, discarding history.
+    pub fn init(&self) {
+        let state = self.snapshot();
+        self.send("INIT", json!({ "payload": to_string(&state) }));
+
+        *self.inner.history.borrow_mut() =
+            vec![Step::new(json!({ "type": INIT_ACTION }), state, None)];
+        self.inner.skipped.borrow_mut().clear();
+        self.inner.current.set(0);
+    }
+
+    /// Handle a message from the devtools. Messages from other sources are ignored.
+    pub fn receive(&self, message: &Value) {
+        if message["source"] != EXTENSION_SOURCE {
+            return;
+        }
+
+        match message["type"].as_str() {
+            Some("START") => self.init(),
+            Some("DISPATCH") => self.dispatch(message),
+            _ => {}
+        }
+    }
+
+    fn dispatch(&self, message: &Value) {
+        let payload = &message["payload"];
+        match payload["type"].as_str() {
+            Some("JUMP_TO_STATE") | Some("JUMP_TO_ACTION") => {
+                if let Some(index) = payload["actionId"].as_u64() {
+                    self.inner.current.set(index as usize);
+                }
+
+                let state = message["state"]
+                    .as_str()
+                    .and_then(|x| serde_json::from_str::<Map<String, Value>>(x).ok());
+                match state {
+                    Some(state) => self.restore(state),
+                    None => crate::log::error!("Devtools sent invalid state: {}", message["state"]),
+                }
+            }
+            Some("TOGGLE_ACTION") => {
+                let Some(id) = payload["id"].as_u64() else {
+                    return;
+                };
+
+                let id = id as usize;
+                // The initial state can't be skipped.
+                if id > 0 && id < self.inner.history.borrow().len() {
+                    let mut skipped = self.inner.skipped.borrow_mut();
+                    if !skipped.remove(&id) {
+                        skipped.insert(id);
+                    }
+                }
+
+                self.restore(self.computed_state(self.inner.current.get()));
+                self.send_lifted_state();
+            }
+            Some("IMPORT_STATE") => {
+                self.import(&payload["nextLiftedState"]);
+                self.restore(self.computed_state(self.inner.current.get()));
+                self.send_lifted_state();
+            }
+            _ => {}
+        }
+    }
+
+    fn on_change(&self, name: &'static str) {
+        let state = self.snapshot();
+        let restored = self.inner.restored.borrow_mut().remove(name);
+        if restored.is_some() && restored.as_ref() == state.get(name) {
+            return;
+        }
+
+        let action = json!({ "type": format!("{} changed", name) });
+        self.send(
+            "ACTION",
+            json!({
+                "action": to_string(&json!({ "action": action, "timestamp": 0 })),
+                "payload": to_string(&state),
+            }),
+        );
+
+        let mut history = self.inner.history.borrow_mut();
+        let step = Step::new(action, state, history.last().map(|x| &x.state));
+        history.push(step);
+        self.inner.current.set(history.len() - 1);
+    }
+
+    /// Current state of all tracked stores.
+    fn snapshot(&self) -> Map<String, Value> {
+        self.inner
+            .stores
+            .borrow()
+            .iter()
+            .filter_map(|store| Some((store.name.to_string(), (store.snapshot)(&self.inner.cx)?)))
+            .collect()
+    }
+
+    /// Set every tracked store in `state`, without recording it as a new action.
+    fn restore(&self, mut state: Map<String, Value>) {
+        // Changes from an earlier restore that were never delivered are forgotten.
+        self.inner.restored.borrow_mut().clear();
+        self.inner.cx.batch(|cx| {
+            for store in self.inner.stores.borrow().iter() {
+                let Some(value) = state.remove(store.name) else {
+                    continue;
+                };
+                // Unchanged stores won't notify, so there's nothing to ignore.
+                if (store.snapshot)(cx).as_ref() == Some(&value) {
+                    continue;
+                }
+
+                self.inner
+                    .restored
+                    .borrow_mut()
+                    .insert(store.name, value.clone());
+                if let Err(err) = (store.restore)(cx, value) {
+                    crate::log::error!("Unable to restore {}: {:?}", store.name, err);
+                    self.inner.restored.borrow_mut().remove(store.name);
+                }
+            }
+        });
+    }
+
+    /// State at `index`, with skipped actions left out.
+    fn computed_state(&self, index: usize) -> Map<String, Value> {
+        let history = self.inner.history.borrow();
+        let skipped = self.inner.skipped.borrow();
+        let mut state = Map::new();
+        for (id, step) in history.iter().enumerate().take(index + 1) {
+            if skipped.contains(&id) {
+                continue;
+            }
+
+            for name in &step.changed {
+                if let Some(value) = step.state.get(name) {
+                    state.insert(name.clone(), value.clone());
+                }
+            }
+        }
+
+        state
+    }
+
+    /// Replace history with one exported from the devtools.
+    fn import(&self, lifted: &Value) {
+        let states = lifted["computedStates"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let mut history = Vec::<Step>::with_capacity(states.len());
+        for (id, computed) in states.into_iter().enumerate() {
+            let action = lifted["actionsById"][id.to_string()]["action"].clone();
+            let state = match computed["state"].clone() {
+                Value::Object(state) => state,
+                _ => Map::new(),
+            };
+
+            let step = Step::new(action, state, history.last().map(|x| &x.state));
+            history.push(step);
+        }
+
+        let skipped = lifted["skippedActionIds"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|x| x.as_u64().map(|x| x as usize))
+            .collect();
+        let current = lifted["currentStateIndex"]
+            .as_u64()
+            .map(|x| x as usize)
+            .unwrap_or(history.len().saturating_sub(1));
+
+        *self.inner.history.borrow_mut() = history;
+        *self.inner.skipped.borrow_mut() = skipped;
+        self.inner.current.set(current);
+    }
+
+    fn send_lifted_state(&self) {
+        let history = self.inner.history.borrow();
+        let actions_by_id: Map<String, Value> = history
+            .iter()
+            .enumerate()
+            .map(|(id, step)| {
+                (
+                    id.to_string(),
+                    json!({ "type": "PERFORM_ACTION", "action": step.action, "timestamp": 0 }),
+                )
+            })
+            .collect();
+        let computed_states: Vec<Value> = (0..history.len())
+            .map(|id| json!({ "state": self.computed_state(id) }))
+            .collect();
+
+        let lifted = json!({
+            "actionsById": actions_by_id,
+            "computedStates": computed_states,
+            "committedState": history.first().map(|x| &x.state),
+            "currentStateIndex": self.inner.current.get(),
+            "nextActionId": history.len(),
+            "skippedActionIds": *self.inner.skipped.borrow(),
+            "stagedActionIds": (0..history.len()).collect::<Vec<_>>(),
+            "isLocked": false,
+            "isPaused": false,
+            "monitorState": {},
+        });
+
+        self.send("STATE", json!({ "payload": to_string(&lifted) }));
+    }
+
+    fn send(&self, kind: &str, fields: Value) {
+        let mut message = json!({
+            "type": kind,
+            "source": PAGE_SOURCE,
+            "instanceId": self.inner.name,
+            "name": self.inner.name,
+        });
+        if let (Value::Object(message), Value::Object(fields)) = (&mut message, fields) {
+            message.extend(fields);
+        }
+
+        self.inner.transport.send(message);
+    }
+}
+
+/// The devtools expect payloads to be serialized.
+fn to_string<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// Sends messages to the browser extension with `window.postMessage`.
+#[cfg(target_arch = "wasm32")]
+pub struct PostMessage;
+
+#[cfg(target_arch = "wasm32")]
+impl Transport for PostMessage {
+    fn send(&self, message: Value) {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+
+        let message = match js_sys::JSON::parse(&message.to_string()) {
+            Ok(message) => message,
+            Err(err) => {
+                crate::log::error!("Unable to convert devtools message: {:?}", err);
+                return;
+            }
+        };
+
+        if let Err(err) = window.post_message(&message, "*") {
+            crate::log::error!("Unable to send devtools message: {:?}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    struct TestState2(u32);
+    impl Store for TestState2 {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Memory(Rc<RefCell<Vec<Value>>>);
+    impl Transport for Memory {
+        fn send(&self, message: Value) {
+            self.0.borrow_mut().push(message);
+        }
+    }
+
+    impl Memory {
+        fn last(&self) -> Value {
+            self.0.borrow().last().cloned().unwrap()
+        }
+
+        fn payload(&self) -> Value {
+            serde_json::from_str(self.last()["payload"].as_str().unwrap()).unwrap()
+        }
+    }
+
+    fn setup() -> (Context, DevTools, Memory) {
+        let cx = Context::new();
+        let transport = Memory::default();
+        let devtools = DevTools::new(&cx, transport.clone());
+        devtools.track::<TestState>();
+        devtools.track::<TestState2>();
+        devtools.init();
+
+        (cx, devtools, transport)
+    }
+
+    fn dispatch(payload: Value, state: Option<Value>) -> Value {
+        json!({
+            "source": EXTENSION_SOURCE,
+            "type": "DISPATCH",
+            "payload": payload,
+            "state": state.map(|x| x.to_string()),
+        })
+    }
+
+    #[test]
+    fn sends_init() {
+        let (_cx, _devtools, transport) = setup();
+
+        assert_eq!(transport.last()["type"], "INIT");
+        assert_eq!(transport.last()["source"], PAGE_SOURCE);
+        assert_eq!(
+            transport.payload(),
+            json!({ type_name::<TestState>(): 0, type_name::<TestState2>(): 0 })
+        );
+    }
+
+    #[test]
+    fn sends_action_with_state() {
+        let (cx, _devtools, transport) = setup();
+
+        cx.set(TestState(1));
+
+        let message = transport.last();
+        assert_eq!(message["type"], "ACTION");
+        let action: Value = serde_json::from_str(message["action"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            action["action"]["type"],
+            format!("{} changed", type_name::<TestState>())
+        );
+        assert_eq!(transport.payload()[type_name::<TestState>()], 1);
+    }
+
+    #[test]
+    fn jump_to_state_sets_stores() {
+        let (cx, devtools, transport) = setup();
+        cx.set(TestState(1));
+        cx.set(TestState2(2));
+        let sent = transport.0.borrow().len();
+
+        devtools.receive(&dispatch(
+            json!({ "type": "JUMP_TO_STATE", "actionId": 1, "index": 1 }),
+            Some(json!({ type_name::<TestState>(): 1, type_name::<TestState2>(): 0 })),
+        ));
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+        assert_eq!(cx.get::<TestState2>().0, 0);
+        // Time travel is not sent back as a new action.
+        assert_eq!(transport.0.borrow().len(), sent);
+    }
+
+    #[test]
+    fn jump_to_state_is_not_recorded_with_scheduler() {
+        let (cx, devtools, transport) = setup();
+        let timer = crate::scheduler::ManualTimer::new();
+        cx.set_timer(timer.clone());
+        cx.set_store_scheduler::<TestState>(crate::scheduler::NotifyScheduler::Microtask);
+        cx.set(TestState(1));
+        timer.run_microtasks();
+        let sent = transport.0.borrow().len();
+
+        devtools.receive(&dispatch(
+            json!({ "type": "JUMP_TO_STATE", "actionId": 0, "index": 0 }),
+            Some(json!({ type_name::<TestState>(): 0, type_name::<TestState2>(): 0 })),
+        ));
+        timer.run_microtasks();
+
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(transport.0.borrow().len(), sent);
+
+        cx.set(TestState(2));
+        timer.run_microtasks();
+
+        assert_eq!(transport.0.borrow().len(), sent + 1);
+    }
+
+    #[test]
+    fn toggle_action_skips_its_changes() {
+        let (cx, devtools, transport) = setup();
+        cx.set(TestState(1));
+        cx.set(TestState2(2));
+
+        devtools.receive(&dispatch(json!({ "type": "TOGGLE_ACTION", "id": 1 }), None));
+
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(cx.get::<TestState2>().0, 2);
+        assert_eq!(transport.last()["type"], "STATE");
+        assert_eq!(transport.payload()["skippedActionIds"], json!([1]));
+
+        devtools.receive(&dispatch(json!({ "type": "TOGGLE_ACTION", "id": 1 }), None));
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+        assert_eq!(transport.payload()["skippedActionIds"], json!([]));
+    }
+
+    #[test]
+    fn import_state_replaces_history() {
+        let (cx, devtools, transport) = setup();
+        let a = type_name::<TestState>();
+        let b = type_name::<TestState2>();
+
+        devtools.receive(&dispatch(
+            json!({
+                "type": "IMPORT_STATE",
+                "nextLiftedState": {
+                    "actionsById": {
+                        "0": { "action": { "type": INIT_ACTION } },
+                        "1": { "action": { "type": "a" } },
+                        "2": { "action": { "type": "b" } },
+                    },
+                    "computedStates": [
+                        { "state": { a: 0, b: 0 } },
+                        { "state": { a: 5, b: 0 } },
+                        { "state": { a: 5, b: 6 } },
+                    ],
+                    "currentStateIndex": 2,
+                    "skippedActionIds": [],
+                },
+            }),
+            None,
+        ));
+
+        assert_eq!(cx.get::<TestState>().0, 5);
+        assert_eq!(cx.get::<TestState2>().0, 6);
+        assert_eq!(transport.payload()["nextActionId"], 3);
+    }
+
+    #[test]
+    fn ignores_other_sources() {
+        let (cx, devtools, _transport) = setup();
+        cx.set(TestState(1));
+
+        devtools.receive(&json!({
+            "source": "someone-else",
+            "type": "DISPATCH",
+            "payload": { "type": "TOGGLE_ACTION", "id": 1 },
+        }));
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+    }
+
+    #[test]
+    fn start_sends_init() {
+        let (cx, devtools, transport) = setup();
+        cx.set(TestState(1));
+
+        devtools.receive(&json!({ "source": EXTENSION_SOURCE, "type": "START" }));
+
+        assert_eq!(transport.last()["type"], "INIT");
+        assert_eq!(transport.payload()[type_name::<TestState>()], 1);
+    }
+}
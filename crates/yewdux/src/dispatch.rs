@@ -64,16 +64,24 @@
 //! ```
 //!
 
-use std::{future::Future, rc::Rc};
+use std::{future::Future, hash::Hash, rc::Rc};
 
+use serde::{de::DeserializeOwned, Serialize};
 use yew::Callback;
 
 use crate::{
     context::Context,
-    store::{Reducer, Store},
+    effect::EffectReducer,
+    family::{KeyedStore, StoreFamily},
+    lens::{Lens, LensDispatch},
+    selector::Selection,
+    store::{Reducer, Store, TryReducer},
     subscriber::{Callable, SubscriberId},
 };
 
+#[cfg(feature = "future")]
+use crate::future::{AsyncReducer, FutureHandle, Overlap};
+
 /// The primary interface to a [`Store`].
 pub struct Dispatch<S: Store> {
     pub(crate) _subscriber_id: Option<Rc<SubscriberId<S>>>,
@@ -112,6 +120,15 @@ impl<S: Store> Dispatch<S> {
         }
     }
 
+    /// Create a dispatch for the instance of `S` with given key. See [StoreFamily].
+    pub fn keyed<K>(cx: &Context, key: K) -> Self
+    where
+        K: Hash + Eq + Clone + 'static,
+        S: KeyedStore<K>,
+    {
+        StoreFamily::new(cx).dispatch(key)
+    }
+
     /// Get the context used by this dispatch.
     pub fn context(&self) -> &Context {
         &self.cx
@@ -150,6 +167,58 @@ impl<S: Store> Dispatch<S> {
         Callback::from(move |e| dispatch.spawn_future(|dispatch| f(dispatch, e)))
     }
 
+    /// Apply an [`AsyncReducer`](crate::future::AsyncReducer). It receives current state, and the
+    /// reducer it resolves to is applied when it completes. Overlapping calls all run to
+    /// completion; see [Self::apply_future_with] for other options.
+    ///
+    /// The returned handle may be awaited, or used to cancel the future.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use yew::prelude::*;
+    /// # use yewdux::{future::AsyncReducer, prelude::*};
+    /// #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// struct State {
+    ///     count: u32,
+    /// }
+    ///
+    /// struct FetchCount;
+    /// impl AsyncReducer<State> for FetchCount {
+    ///     type Reducer = Box<dyn FnOnce(Rc<State>) -> Rc<State>>;
+    ///
+    ///     async fn apply(self, _state: Rc<State>) -> Self::Reducer {
+    ///         // Fetch the new count here.
+    ///         let count = 42;
+    ///         Box::new(move |_| State { count }.into())
+    ///     }
+    /// }
+    ///
+    /// # #[hook]
+    /// # fn use_foo() {
+    /// let dispatch = use_dispatch::<State>();
+    /// let onclick = Callback::from(move |_: MouseEvent| {
+    ///     dispatch.apply_future(FetchCount);
+    /// });
+    /// # }
+    /// ```
+    #[cfg(feature = "future")]
+    pub fn apply_future<R>(&self, reducer: R) -> FutureHandle
+    where
+        R: AsyncReducer<S>,
+    {
+        self.apply_future_with(reducer, Overlap::TakeEvery)
+    }
+
+    /// Like [Self::apply_future], with a policy for calls made while another future of the same
+    /// reducer type is still running for this store.
+    #[cfg(feature = "future")]
+    pub fn apply_future_with<R>(&self, reducer: R, overlap: Overlap) -> FutureHandle
+    where
+        R: AsyncReducer<S>,
+    {
+        crate::future::apply_future(&self.cx, reducer, overlap)
+    }
+
     /// Create a dispatch that subscribes to changes in state. Latest state is sent immediately,
     /// and on every subsequent change. Automatically unsubscribes when this dispatch is dropped.
     /// 
@@ -262,6 +331,46 @@ impl<S: Store> Dispatch<S> {
         }
     }
 
+    /// Subscribe to the value `selector` returns. `on_change` is called only when that value
+    /// changes, and not for the initial value. Unsubscribes when the returned [Selection] is
+    /// dropped.
+    pub fn subscribe_selector<R, F, C>(&self, selector: F, on_change: C) -> Selection<S, R>
+    where
+        R: PartialEq + 'static,
+        F: Fn(&S) -> R + 'static,
+        C: Callable<R>,
+    {
+        self.cx.select(selector, on_change)
+    }
+
+    /// Similar to [Self::subscribe_selector], with a custom equality check for the selected
+    /// value.
+    pub fn subscribe_selector_eq<R, F, E, C>(
+        &self,
+        selector: F,
+        eq: E,
+        on_change: C,
+    ) -> Selection<S, R>
+    where
+        R: 'static,
+        F: Fn(&S) -> R + 'static,
+        E: Fn(&R, &R) -> bool + 'static,
+        C: Callable<R>,
+    {
+        self.cx.select_eq(selector, eq, on_change)
+    }
+
+    /// Create a dispatch for the part of this store seen through `lens`. See the
+    /// [lens module](crate::lens) for an example.
+    pub fn lens<C, L>(&self, lens: L) -> LensDispatch<C>
+    where
+        S: Clone,
+        C: PartialEq + 'static,
+        L: Lens<S, C>,
+    {
+        LensDispatch::new(&self.cx, lens)
+    }
+
     /// Get the current state.
     pub fn get(&self) -> Rc<S> {
         self.cx.get::<S>()
@@ -301,6 +410,206 @@ impl<S: Store> Dispatch<S> {
         self.cx.reduce(reducer);
     }
 
+    /// Like [Self::apply], but the reducer is also recorded in the context's
+    /// [`ActionLog`](crate::action_log::ActionLog), so it may be replayed later.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use serde::{Deserialize, Serialize};
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// struct State {
+    ///     count: u32,
+    /// }
+    ///
+    /// #[derive(Serialize, Deserialize)]
+    /// struct AddOne;
+    /// impl Reducer<State> for AddOne {
+    ///     fn apply(self, state: Rc<State>) -> Rc<State> {
+    ///         State {
+    ///             count: state.count + 1,
+    ///         }
+    ///         .into()
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// dispatch.apply_recorded(AddOne);
+    /// assert_eq!(dispatch.context().action_log().len(), 1);
+    /// # }
+    /// ```
+    pub fn apply_recorded<R>(&self, reducer: R)
+    where
+        R: Reducer<S> + Serialize + DeserializeOwned,
+    {
+        self.cx.reduce_recorded(reducer);
+    }
+
+    /// Like [Self::apply_callback], but the reducer is also recorded in the context's
+    /// [`ActionLog`](crate::action_log::ActionLog).
+    pub fn apply_recorded_callback<E, M, F>(&self, f: F) -> Callback<E>
+    where
+        M: Reducer<S> + Serialize + DeserializeOwned,
+        F: Fn(E) -> M + 'static,
+    {
+        let context = self.cx.clone();
+        Callback::from(move |e| {
+            let msg = f(e);
+            context.reduce_recorded(msg);
+        })
+    }
+
+    /// Apply an [`EffectReducer`](crate::effect::EffectReducer) immediately. Its effects run once
+    /// subscribers have been notified.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// use yewdux::effect::{Effect, EffectReducer};
+    ///
+    /// #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// struct State {
+    ///     count: u32,
+    /// }
+    ///
+    /// struct AddOne;
+    /// impl EffectReducer<State> for AddOne {
+    ///     fn apply(self, state: Rc<State>) -> (Rc<State>, Vec<Effect>) {
+    ///         let state = State { count: state.count + 1 };
+    ///         let log = Effect::reduce::<State, _>(|state: Rc<State>| {
+    ///             println!("count is now {}", state.count);
+    ///             state
+    ///         });
+    ///
+    ///         (state.into(), vec![log])
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// dispatch.apply_effects(AddOne);
+    /// # }
+    /// ```
+    pub fn apply_effects<R: EffectReducer<S>>(&self, reducer: R) {
+        self.cx.reduce_effects(reducer);
+    }
+
+    /// Like [Self::apply_callback], but for an
+    /// [`EffectReducer`](crate::effect::EffectReducer).
+    pub fn apply_effects_callback<E, M, F>(&self, f: F) -> Callback<E>
+    where
+        M: EffectReducer<S>,
+        F: Fn(E) -> M + 'static,
+    {
+        let context = self.cx.clone();
+        Callback::from(move |e| {
+            let msg = f(e);
+            context.reduce_effects(msg);
+        })
+    }
+
+    /// Apply a [`TryReducer`](crate::store::TryReducer) immediately. If it fails, the error is
+    /// returned, state is left untouched, and subscribers are not notified.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// struct State {
+    ///     count: u32,
+    /// }
+    ///
+    /// struct SubOne;
+    /// impl TryReducer<State> for SubOne {
+    ///     type Error = &'static str;
+    ///
+    ///     fn try_apply(self, state: Rc<State>) -> Result<Rc<State>, Self::Error> {
+    ///         let count = state.count.checked_sub(1).ok_or("count is already zero")?;
+    ///         Ok(State { count }.into())
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// assert_eq!(dispatch.try_apply(SubOne), Err("count is already zero"));
+    /// # }
+    /// ```
+    pub fn try_apply<R: TryReducer<S>>(&self, reducer: R) -> Result<(), R::Error> {
+        self.cx.try_reduce(reducer)
+    }
+
+    /// Create a callback that applies a [`TryReducer`](crate::store::TryReducer). The callback
+    /// returns the result of the reducer.
+    ///
+    /// ```
+    /// # use std::rc::Rc;
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// # #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// # struct State {
+    /// #     count: u32,
+    /// # }
+    /// # struct SubOne;
+    /// # impl TryReducer<State> for SubOne {
+    /// #     type Error = &'static str;
+    /// #     fn try_apply(self, state: Rc<State>) -> Result<Rc<State>, Self::Error> {
+    /// #         let count = state.count.checked_sub(1).ok_or("count is already zero")?;
+    /// #         Ok(State { count }.into())
+    /// #     }
+    /// # }
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// let sub_one = dispatch.try_apply_callback(|_| SubOne);
+    /// assert!(sub_one.emit(()).is_err());
+    /// # }
+    /// ```
+    pub fn try_apply_callback<E, M, F>(&self, f: F) -> Callback<E, Result<(), M::Error>>
+    where
+        M: TryReducer<S>,
+        F: Fn(E) -> M + 'static,
+    {
+        let context = self.cx.clone();
+        Callback::from(move |e| {
+            let msg = f(e);
+            context.try_reduce(msg)
+        })
+    }
+
+    /// Apply all changes made in `f`, notifying subscribers once per changed store after it
+    /// returns. See [Context::batch].
+    ///
+    /// ```
+    /// # use yewdux::prelude::*;
+    /// # #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// # struct State {
+    /// #     count: u32,
+    /// # }
+    /// # fn main() {
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// // Subscribers are only notified once, with `count == 2`.
+    /// dispatch.batch(|dispatch| {
+    ///     dispatch.reduce_mut(|state| state.count += 1);
+    ///     dispatch.reduce_mut(|state| state.count += 1);
+    /// });
+    /// # }
+    /// ```
+    pub fn batch<R, F: FnOnce(&Self) -> R>(&self, f: F) -> R {
+        self.cx.batch(|_| f(self))
+    }
+
     /// Create a callback that applies a [`Reducer`](crate::store::Reducer).
     ///
     /// ```
@@ -367,6 +676,28 @@ impl<S: Store> Dispatch<S> {
         self.cx.set(val);
     }
 
+    /// Reinitialize state with [Store::new].
+    ///
+    /// ```
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// # #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// # struct State {
+    /// #     count: u32,
+    /// # }
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// dispatch.set(State { count: 1 });
+    /// dispatch.reset();
+    /// assert_eq!(dispatch.get().count, 0);
+    /// # }
+    /// ```
+    pub fn reset(&self) {
+        self.cx.reset::<S>();
+    }
+
     /// Set state using value from callback.
     ///
     /// ```
@@ -525,6 +856,43 @@ impl<S: Store> Dispatch<S> {
         result.expect("result not initialized")
     }
 
+    /// Mutate state with given function, keeping the changes only if it succeeds. If it fails,
+    /// the error is returned, state is left untouched, and subscribers are not notified.
+    ///
+    /// ```
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// # #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// # struct State {
+    /// #     count: u32,
+    /// # }
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// let result = dispatch.try_reduce_mut(|state| {
+    ///     state.count = "forty two".parse()?;
+    ///     Ok::<_, std::num::ParseIntError>(())
+    /// });
+    ///
+    /// assert!(result.is_err());
+    /// # }
+    /// ```
+    pub fn try_reduce_mut<F, R, Error>(&self, f: F) -> Result<R, Error>
+    where
+        S: Clone,
+        F: FnOnce(&mut S) -> Result<R, Error>,
+    {
+        let mut result = None;
+
+        self.cx.try_reduce_mut(|x| {
+            result = Some(f(x)?);
+            Ok(())
+        })?;
+
+        Ok(result.expect("result not initialized"))
+    }
+
     /// Like [Self::reduce_mut] but from a callback.
     ///
     /// ```
@@ -597,6 +965,69 @@ impl<S: Store> Dispatch<S> {
             });
         })
     }
+
+    /// Like [Self::try_reduce_mut] but from a callback. The callback returns the result of `f`.
+    ///
+    /// ```
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// # #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// # struct State {
+    /// #     count: u32,
+    /// # }
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// let sub_one = dispatch.try_reduce_mut_callback(|state| {
+    ///     state.count = state.count.checked_sub(1).ok_or("count is already zero")?;
+    ///     Ok(())
+    /// });
+    /// assert_eq!(sub_one.emit(()), Err("count is already zero"));
+    /// # }
+    /// ```
+    pub fn try_reduce_mut_callback<F, R, Error, E>(&self, f: F) -> Callback<E, Result<R, Error>>
+    where
+        S: Clone,
+        F: Fn(&mut S) -> Result<R, Error> + 'static,
+        E: 'static,
+    {
+        let dispatch = self.clone();
+        Callback::from(move |_| dispatch.try_reduce_mut(&f))
+    }
+
+    /// Similar to [Self::try_reduce_mut_callback] but also provides the fired event.
+    ///
+    /// ```
+    /// # use yew::prelude::*;
+    /// # use yewdux::prelude::*;
+    /// # #[derive(Default, Clone, PartialEq, Eq, Store)]
+    /// # struct State {
+    /// #     count: u32,
+    /// # }
+    /// # fn main() {
+    /// # // Context handling code is omitted for clarity
+    /// # let cx = yewdux::Context::new();
+    /// # let dispatch = Dispatch::<State>::new(&cx);
+    /// let onchange = dispatch.try_reduce_mut_callback_with(|state, event: Event| {
+    ///     let value = event.target_unchecked_into::<web_sys::HtmlInputElement>().value();
+    ///     state.count = value.parse()?;
+    ///     Ok::<_, std::num::ParseIntError>(())
+    /// });
+    /// # }
+    /// ```
+    pub fn try_reduce_mut_callback_with<F, R, Error, E>(
+        &self,
+        f: F,
+    ) -> Callback<E, Result<R, Error>>
+    where
+        S: Clone,
+        F: Fn(&mut S, E) -> Result<R, Error> + 'static,
+        E: 'static,
+    {
+        let dispatch = self.clone();
+        Callback::from(move |e: E| dispatch.try_reduce_mut(|x| f(x, e)))
+    }
 }
 
 impl<S: Store> Clone for Dispatch<S> {
@@ -828,6 +1259,82 @@ mod tests {
         assert!(dispatch.get() != old)
     }
 
+    struct FailingMsg;
+    impl TryReducer<TestState> for FailingMsg {
+        type Error = &'static str;
+
+        fn try_apply(self, _state: Rc<TestState>) -> Result<Rc<TestState>, Self::Error> {
+            Err("failed")
+        }
+    }
+
+    #[test]
+    fn dispatch_try_apply_works() {
+        let dispatch = Dispatch::<TestState>::new(&Context::new());
+        let old = dispatch.get();
+
+        dispatch
+            .try_apply(|state: Rc<TestState>| Ok::<_, ()>(TestState(state.0 + 1).into()))
+            .unwrap();
+
+        assert!(dispatch.get() != old)
+    }
+
+    #[test]
+    fn dispatch_try_apply_returns_error() {
+        let dispatch = Dispatch::<TestState>::new(&Context::new());
+        let old = dispatch.get();
+
+        assert_eq!(dispatch.try_apply(FailingMsg), Err("failed"));
+        assert!(Rc::ptr_eq(&dispatch.get(), &old))
+    }
+
+    #[test]
+    fn dispatch_try_apply_callback_returns_error() {
+        let dispatch = Dispatch::<TestState>::new(&Context::new());
+
+        let cb = dispatch.try_apply_callback(|_| FailingMsg);
+
+        assert_eq!(cb.emit(()), Err("failed"));
+    }
+
+    #[test]
+    fn dispatch_try_reduce_mut_returns_value() {
+        let dispatch = Dispatch::<TestState>::new(&Context::new());
+
+        let result = dispatch.try_reduce_mut(|state| {
+            state.0 += 1;
+            Ok::<_, ()>(state.0)
+        });
+
+        assert_eq!(result, Ok(1));
+        assert_eq!(dispatch.get().0, 1);
+    }
+
+    #[test]
+    fn dispatch_try_reduce_mut_callback_with_leaves_state_on_error() {
+        let dispatch = Dispatch::<TestState>::new(&Context::new());
+        let flag = Mrc::new(false);
+        let _id = {
+            let flag = flag.clone();
+            Dispatch::<TestState>::new(dispatch.context())
+                .subscribe_silent(move |_| *flag.borrow_mut() = true)
+        };
+
+        let cb = dispatch.try_reduce_mut_callback_with(|state, val: u32| {
+            state.0 += val;
+            if state.0 > 1 {
+                Err("too big")
+            } else {
+                Ok(())
+            }
+        });
+
+        assert_eq!(cb.emit(2), Err("too big"));
+        assert_eq!(dispatch.get().0, 0);
+        assert!(!*flag.borrow());
+    }
+
     #[test]
     fn subscriber_is_notified() {
         let cx = Context::new();
@@ -846,6 +1353,24 @@ mod tests {
         assert!(*flag.borrow());
     }
 
+    #[test]
+    fn dispatch_batch_notifies_once() {
+        let cx = Context::new();
+        let calls = Mrc::new(0);
+
+        let _id = {
+            let calls = calls.clone();
+            Dispatch::<TestState>::new(&cx).subscribe_silent(move |_| *calls.borrow_mut() += 1)
+        };
+
+        Dispatch::<TestState>::new(&cx).batch(|dispatch| {
+            dispatch.reduce_mut(|state| state.0 += 1);
+            dispatch.apply(Msg);
+        });
+
+        assert_eq!(*calls.borrow(), 1);
+    }
+
     #[test]
     fn subscriber_is_not_notified_when_state_is_same() {
         let cx = Context::new();
@@ -874,15 +1399,15 @@ mod tests {
         let cx = Context::new();
         let entry = cx.get_or_init_default::<Mrc<Subscribers<TestState>>>();
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
 
         let dispatch = Dispatch::<TestState>::new(&cx).subscribe(|_| ());
 
-        assert!(!entry.store.borrow().borrow().0.is_empty());
+        assert!(!entry.store.borrow().borrow().slab.is_empty());
 
         drop(dispatch);
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
     }
 
     #[test]
@@ -890,19 +1415,19 @@ mod tests {
         let cx = Context::new();
         let entry = cx.get_or_init_default::<Mrc<Subscribers<TestState>>>();
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
 
         let dispatch = Dispatch::<TestState>::new(&cx).subscribe(|_| ());
         let dispatch_clone = dispatch.clone();
 
-        assert!(!entry.store.borrow().borrow().0.is_empty());
+        assert!(!entry.store.borrow().borrow().slab.is_empty());
 
         drop(dispatch_clone);
 
-        assert!(!entry.store.borrow().borrow().0.is_empty());
+        assert!(!entry.store.borrow().borrow().slab.is_empty());
 
         drop(dispatch);
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
     }
 }
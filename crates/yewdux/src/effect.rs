@@ -0,0 +1,377 @@
+//! Side effects returned from reducers.
+//!
+//! An [EffectReducer] returns new state together with a list of [Effect]s, like follow-up
+//! reducers or futures. The context runs them after subscribers are notified. Because effects are
+//! plain values, reducers can be tested by inspecting the effects they return, without running
+//! them.
+//!
+//! ```
+//! use std::rc::Rc;
+//!
+//! use yewdux::{effect::{Effect, EffectReducer}, prelude::*};
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Search {
+//!     query: String,
+//!     loading: bool,
+//! }
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Msg {
+//!     Search(String),
+//!     Loaded,
+//! }
+//!
+//! impl Reducer<Search> for Msg {
+//!     fn apply(self, state: Rc<Search>) -> Rc<Search> {
+//!         let (state, _) = EffectReducer::apply(self, state);
+//!         state
+//!     }
+//! }
+//!
+//! impl EffectReducer<Search> for Msg {
+//!     fn apply(self, state: Rc<Search>) -> (Rc<Search>, Vec<Effect>) {
+//!         match self {
+//!             Msg::Search(query) => (
+//!                 Search { query, loading: true }.into(),
+//!                 // Fetch results here.
+//!                 vec![Effect::reduce::<Search, _>(Msg::Loaded)],
+//!             ),
+//!             Msg::Loaded => (
+//!                 Search { loading: false, ..(*state).clone() }.into(),
+//!                 vec![],
+//!             ),
+//!         }
+//!     }
+//! }
+//!
+//! // Effects may be inspected instead of executed.
+//! let (state, effects) = EffectReducer::apply(Msg::Search("yew".into()), Default::default());
+//! assert!(state.loading);
+//! assert_eq!(effects[0].reducer::<Msg>(), Some(&Msg::Loaded));
+//!
+//! // Or run by the context.
+//! let cx = yewdux::Context::new();
+//! Dispatch::<Search>::new(&cx).apply_effects(Msg::Search("yew".into()));
+//! assert!(!cx.get::<Search>().loading);
+//! ```
+use std::{
+    any::{type_name, Any},
+    rc::Rc,
+};
+
+#[cfg(feature = "future")]
+use std::{future::Future, pin::Pin};
+
+use crate::{
+    store::{Reducer, Store},
+    Context,
+};
+
+/// A reducer that also returns side effects.
+pub trait EffectReducer<S> {
+    /// Return new state, and effects to run once subscribers have been notified.
+    fn apply(self, state: Rc<S>) -> (Rc<S>, Vec<Effect>);
+}
+
+/// The kind of an [Effect].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectKind {
+    /// A reducer applied to a store.
+    Reduce,
+    /// A future spawned with access to the context.
+    #[cfg(feature = "future")]
+    Future,
+}
+
+type ApplyReducer = fn(&Context, Box<dyn Any>);
+
+#[cfg(feature = "future")]
+type SpawnFuture = Box<dyn FnOnce(Context) -> Pin<Box<dyn Future<Output = ()>>>>;
+
+enum Inner {
+    Reduce {
+        store: &'static str,
+        reducer: Box<dyn Any>,
+        apply: ApplyReducer,
+    },
+    #[cfg(feature = "future")]
+    Future(SpawnFuture),
+}
+
+/// A side effect, run by the context after subscribers are notified.
+pub struct Effect {
+    inner: Inner,
+}
+
+impl std::fmt::Debug for Effect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Effect")
+            .field("kind", &self.kind())
+            .field("store", &self.store_name())
+            .finish()
+    }
+}
+
+impl Effect {
+    /// Apply `reducer` to store `S`.
+    pub fn reduce<S, R>(reducer: R) -> Self
+    where
+        S: Store,
+        R: Reducer<S> + 'static,
+    {
+        Self {
+            inner: Inner::Reduce {
+                store: type_name::<S>(),
+                reducer: Box::new(reducer),
+                apply: apply_reducer::<S, R>,
+            },
+        }
+    }
+
+    /// Spawn a future with access to the context.
+    #[cfg(feature = "future")]
+    pub fn future<F, FU>(f: F) -> Self
+    where
+        F: FnOnce(Context) -> FU + 'static,
+        FU: Future<Output = ()> + 'static,
+    {
+        Self {
+            inner: Inner::Future(Box::new(move |cx| Box::pin(f(cx)))),
+        }
+    }
+
+    /// What this effect does when run.
+    pub fn kind(&self) -> EffectKind {
+        match self.inner {
+            Inner::Reduce { .. } => EffectKind::Reduce,
+            #[cfg(feature = "future")]
+            Inner::Future(_) => EffectKind::Future,
+        }
+    }
+
+    /// Type name of the store a reducer effect applies to.
+    pub fn store_name(&self) -> Option<&'static str> {
+        match &self.inner {
+            Inner::Reduce { store, .. } => Some(store),
+            #[cfg(feature = "future")]
+            Inner::Future(_) => None,
+        }
+    }
+
+    /// The reducer of a reducer effect, if it has type `R`.
+    pub fn reducer<R: 'static>(&self) -> Option<&R> {
+        match &self.inner {
+            Inner::Reduce { reducer, .. } => reducer.downcast_ref(),
+            #[cfg(feature = "future")]
+            Inner::Future(_) => None,
+        }
+    }
+
+    /// Run this effect now. Futures are spawned, so they complete later.
+    pub fn run(self, cx: &Context) {
+        match self.inner {
+            Inner::Reduce { reducer, apply, .. } => apply(cx, reducer),
+            #[cfg(feature = "future")]
+            Inner::Future(spawn) => yew::platform::spawn_local(spawn(cx.clone())),
+        }
+    }
+}
+
+fn apply_reducer<S: Store, R: Reducer<S> + 'static>(cx: &Context, reducer: Box<dyn Any>) {
+    let reducer = reducer
+        .downcast::<R>()
+        .expect("Effect reducer type mismatch");
+    cx.reduce::<S, R>(*reducer);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, time::Duration};
+
+    use super::*;
+    use crate::{
+        scheduler::{ManualTimer, NotifyScheduler},
+        Dispatch,
+    };
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState2(u32);
+    impl Store for TestState2 {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct SetTo(u32);
+    impl Reducer<TestState2> for SetTo {
+        fn apply(self, _state: Rc<TestState2>) -> Rc<TestState2> {
+            TestState2(self.0).into()
+        }
+    }
+
+    /// Adds one, then sets `TestState2` to the new value.
+    struct AddOne;
+    impl EffectReducer<TestState> for AddOne {
+        fn apply(self, state: Rc<TestState>) -> (Rc<TestState>, Vec<Effect>) {
+            let value = state.0 + 1;
+            (
+                TestState(value).into(),
+                vec![Effect::reduce::<TestState2, _>(SetTo(value))],
+            )
+        }
+    }
+
+    #[test]
+    fn effects_can_be_inspected() {
+        let (state, effects) = AddOne.apply(TestState(1).into());
+
+        assert_eq!(state.0, 2);
+        assert_eq!(effects.len(), 1);
+        assert_eq!(effects[0].kind(), EffectKind::Reduce);
+        assert_eq!(effects[0].store_name(), Some(type_name::<TestState2>()));
+        assert_eq!(effects[0].reducer::<SetTo>(), Some(&SetTo(2)));
+    }
+
+    #[test]
+    fn effects_run_after_notification() {
+        let cx = Context::new();
+        let seen = Rc::new(Cell::new(None));
+        let _id = {
+            let seen = seen.clone();
+            let cx = cx.clone();
+            cx.clone()
+                .subscribe_silent::<TestState, _>(move |_| seen.set(Some(cx.get::<TestState2>().0)))
+        };
+
+        Dispatch::<TestState>::new(&cx).apply_effects(AddOne);
+
+        // The effect hadn't run yet when subscribers were notified.
+        assert_eq!(seen.get(), Some(0));
+        assert_eq!(cx.get::<TestState2>().0, 1);
+    }
+
+    #[test]
+    fn effects_run_after_batch() {
+        let cx = Context::new();
+
+        cx.batch(|cx| {
+            cx.reduce_effects(AddOne);
+            assert_eq!(cx.get::<TestState2>().0, 0);
+        });
+
+        assert_eq!(cx.get::<TestState2>().0, 1);
+    }
+
+    #[test]
+    fn effects_are_dropped_when_change_is_vetoed() {
+        struct Veto;
+        impl crate::middleware::Middleware for Veto {
+            fn on_reduce(&self, _cx: &Context, change: &mut crate::middleware::Change) {
+                change.veto();
+            }
+        }
+
+        let cx = Context::new();
+        cx.add_middleware(Veto);
+
+        cx.reduce_effects(AddOne);
+        cx.batch(|cx| cx.reduce_effects(AddOne));
+
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(cx.get::<TestState2>().0, 0);
+    }
+
+    #[test]
+    fn effects_run_after_queued_notification() {
+        #[derive(Clone, PartialEq, Eq)]
+        struct Trigger;
+        impl Store for Trigger {
+            fn new(_cx: &Context) -> Self {
+                Self
+            }
+
+            fn should_notify(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        let cx = Context::new();
+        cx.set_dispatch_mode(crate::context::DispatchMode::Queued);
+        let seen = Rc::new(Cell::new(None));
+        let _state = {
+            let seen = seen.clone();
+            let cx = cx.clone();
+            cx.clone()
+                .subscribe_silent::<TestState, _>(move |_| seen.set(Some(cx.get::<TestState2>().0)))
+        };
+        let _trigger = {
+            let cx = cx.clone();
+            cx.clone()
+                .subscribe_silent::<Trigger, _>(move |_| cx.reduce_effects(AddOne))
+        };
+
+        cx.set(Trigger);
+
+        assert_eq!(seen.get(), Some(0));
+        assert_eq!(cx.get::<TestState2>().0, 1);
+    }
+
+    #[test]
+    fn effects_run_after_scheduled_notification() {
+        let cx = Context::new();
+        let timer = ManualTimer::new();
+        cx.set_timer(timer.clone());
+        cx.set_store_scheduler::<TestState>(NotifyScheduler::Debounce(Duration::from_millis(100)));
+        let seen = Rc::new(Cell::new(None));
+        let _id = {
+            let seen = seen.clone();
+            let cx = cx.clone();
+            cx.clone()
+                .subscribe_silent::<TestState, _>(move |_| seen.set(Some(cx.get::<TestState2>().0)))
+        };
+
+        cx.reduce_effects(AddOne);
+        cx.batch(|cx| cx.reduce_effects(AddOne));
+        assert_eq!(cx.get::<TestState2>().0, 0);
+
+        timer.advance(Duration::from_millis(100));
+        // Subscribers saw the state before the effects of both changes ran.
+        assert_eq!(seen.get(), Some(0));
+        assert_eq!(cx.get::<TestState2>().0, 2);
+    }
+
+    #[cfg(feature = "future")]
+    #[test]
+    fn future_effect_is_spawned() {
+        crate::future::run(|| async {
+            let cx = Context::new();
+            let (tx, rx) = yew::platform::pinned::oneshot::channel();
+            Effect::future(|cx: Context| async move {
+                cx.set(TestState(5));
+                tx.send(()).unwrap();
+            })
+            .run(&cx);
+
+            rx.await.unwrap();
+            assert_eq!(cx.get::<TestState>().0, 5);
+        });
+    }
+}
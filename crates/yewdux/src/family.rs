@@ -0,0 +1,279 @@
+//! Keyed store families, with one instance of a store per key.
+//!
+//! Useful for state like documents or chat rooms, where each instance should have its own
+//! subscribers, so a change to one doesn't re-render components showing another.
+//!
+//! Each instance lives in its own [child](crate::Context::child) context, so any other store is
+//! shared as usual. An instance is dropped once no [Dispatch] for its key remains.
+//!
+//! ```
+//! use yewdux::{family::KeyedStore, prelude::*};
+//!
+//! #[derive(Clone, PartialEq)]
+//! struct Room {
+//!     name: String,
+//!     messages: Vec<String>,
+//! }
+//!
+//! impl Store for Room {
+//!     fn new(_cx: &yewdux::Context) -> Self {
+//!         Self {
+//!             name: Default::default(),
+//!             messages: Default::default(),
+//!         }
+//!     }
+//!
+//!     fn should_notify(&self, old: &Self) -> bool {
+//!         self != old
+//!     }
+//! }
+//!
+//! impl KeyedStore<String> for Room {
+//!     fn new_keyed(_cx: &yewdux::Context, key: &String) -> Self {
+//!         Self {
+//!             name: key.clone(),
+//!             messages: Default::default(),
+//!         }
+//!     }
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! let general = Dispatch::<Room>::keyed(&cx, "general".to_string());
+//! let random = Dispatch::<Room>::keyed(&cx, "random".to_string());
+//!
+//! general.reduce_mut(|room| room.messages.push("Hello".to_string()));
+//!
+//! assert_eq!(general.get().messages.len(), 1);
+//! assert_eq!(random.get().name, "random");
+//! assert!(random.get().messages.is_empty());
+//! ```
+use std::{collections::HashMap, hash::Hash, marker::PhantomData};
+
+use crate::{
+    context::{Context, WeakContext},
+    dispatch::Dispatch,
+    store::Store,
+};
+
+/// A store with one instance per key.
+pub trait KeyedStore<K>: Store {
+    /// Create the instance for `key`. [Store::new] is not called for keyed instances.
+    fn new_keyed(cx: &Context, key: &K) -> Self;
+}
+
+/// Context of every live instance, by key.
+struct Members<K, S> {
+    contexts: HashMap<K, WeakContext>,
+    _marker: PhantomData<S>,
+}
+
+impl<K, S> Default for Members<K, S> {
+    fn default() -> Self {
+        Self {
+            contexts: Default::default(),
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Instances of store `S` in a context, one per key.
+pub struct StoreFamily<K, S> {
+    cx: Context,
+    _marker: PhantomData<(K, S)>,
+}
+
+impl<K, S> std::fmt::Debug for StoreFamily<K, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StoreFamily").finish()
+    }
+}
+
+impl<K, S> Clone for StoreFamily<K, S> {
+    fn clone(&self) -> Self {
+        Self {
+            cx: self.cx.clone(),
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<K, S> StoreFamily<K, S>
+where
+    K: Hash + Eq + Clone + 'static,
+    S: KeyedStore<K>,
+{
+    pub fn new(cx: &Context) -> Self {
+        Self {
+            cx: cx.clone(),
+            _marker: Default::default(),
+        }
+    }
+
+    /// Dispatch for the instance of `key`, creating it with [KeyedStore::new_keyed] if it doesn't
+    /// exist.
+    pub fn dispatch(&self, key: K) -> Dispatch<S> {
+        let members = self.cx.internal::<Members<K, S>>();
+        if let Some(cx) = members
+            .borrow()
+            .contexts
+            .get(&key)
+            .and_then(WeakContext::upgrade)
+        {
+            return Dispatch::new(&cx);
+        }
+
+        let cx = self.cx.child();
+        cx.set_constructor({
+            let key = key.clone();
+            move |cx| S::new_keyed(cx, &key)
+        });
+        cx.get::<S>();
+
+        members.with_mut(|members| {
+            // Good time to forget evicted instances.
+            members.contexts.retain(|_, cx| cx.upgrade().is_some());
+            members.contexts.insert(key, cx.downgrade());
+        });
+
+        Dispatch::new(&cx)
+    }
+
+    /// Keys of all live instances.
+    pub fn keys(&self) -> Vec<K> {
+        self.cx
+            .internal::<Members<K, S>>()
+            .borrow()
+            .contexts
+            .iter()
+            .filter(|(_, cx)| cx.upgrade().is_some())
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Whether an instance for `key` is live.
+    pub fn contains(&self, key: &K) -> bool {
+        self.cx
+            .internal::<Members<K, S>>()
+            .borrow()
+            .contexts
+            .get(key)
+            .is_some_and(|cx| cx.upgrade().is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Keyed(u32);
+    impl Store for Keyed {
+        fn new(_cx: &Context) -> Self {
+            unreachable!()
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    impl KeyedStore<u32> for Keyed {
+        fn new_keyed(_cx: &Context, key: &u32) -> Self {
+            Self(*key)
+        }
+    }
+
+    #[test]
+    fn keys_have_separate_instances() {
+        let cx = Context::new();
+        let one = Dispatch::<Keyed>::keyed(&cx, 1);
+        let two = Dispatch::<Keyed>::keyed(&cx, 2);
+
+        one.reduce_mut(|state| state.0 += 10);
+
+        assert_eq!(one.get().0, 11);
+        assert_eq!(two.get().0, 2);
+        assert_eq!(Dispatch::<Keyed>::keyed(&cx, 1).get().0, 11);
+    }
+
+    #[test]
+    fn keys_have_separate_subscribers() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _one = {
+            let calls = calls.clone();
+            Dispatch::<Keyed>::keyed(&cx, 1).subscribe_silent(move |_| calls.set(calls.get() + 1))
+        };
+        let two = Dispatch::<Keyed>::keyed(&cx, 2);
+
+        two.set(Keyed(5));
+
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn other_stores_are_shared() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<Keyed>::keyed(&cx, 1);
+
+        dispatch.context().set(TestState(1));
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+    }
+
+    #[test]
+    fn instance_is_evicted_without_users() {
+        let cx = Context::new();
+        let family = StoreFamily::<u32, Keyed>::new(&cx);
+        let one = family.dispatch(1);
+        let two = family.dispatch(2);
+        one.set(Keyed(10));
+        let state = one.get();
+
+        drop(one);
+
+        assert_eq!(family.keys(), vec![2]);
+        assert!(!family.contains(&1));
+        assert_eq!(Rc::strong_count(&state), 1);
+        // Created again with `new_keyed`.
+        assert_eq!(family.dispatch(1).get().0, 1);
+        drop(two);
+    }
+
+    #[test]
+    fn reset_creates_instance_with_key() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<Keyed>::keyed(&cx, 7);
+        dispatch.set(Keyed(10));
+
+        dispatch.reset();
+
+        assert_eq!(dispatch.get().0, 7);
+    }
+
+    #[test]
+    fn removed_instance_is_created_again_with_key() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<Keyed>::keyed(&cx, 7);
+        dispatch.set(Keyed(10));
+
+        dispatch.context().remove::<Keyed>();
+
+        assert_eq!(dispatch.get().0, 7);
+        assert_eq!(Dispatch::<Keyed>::keyed(&cx, 7).get().0, 7);
+    }
+}
@@ -1,9 +1,9 @@
 //! The functional interface for Yewdux
-use std::{ops::Deref, rc::Rc};
+use std::{hash::Hash, ops::Deref, rc::Rc};
 
 use yew::functional::*;
 
-use crate::{dispatch::Dispatch, store::Store, Context};
+use crate::{dispatch::Dispatch, family::KeyedStore, lens::LensDispatch, store::Store, Context};
 
 #[hook]
 fn use_cx() -> Context {
@@ -65,6 +65,97 @@ where
     (Rc::clone(&state), dispatch.deref().clone())
 }
 
+/// Similar to [`use_store`], but for the instance of a [KeyedStore] with given key. Changes to
+/// instances with other keys don't trigger a re-render.
+///
+/// # Example
+/// ```
+/// use yew::prelude::*;
+/// use yewdux::prelude::*;
+///
+/// #[derive(Default, Clone, PartialEq, Store)]
+/// struct Room {
+///     messages: Vec<String>,
+/// }
+///
+/// impl KeyedStore<u32> for Room {
+///     fn new_keyed(_cx: &yewdux::Context, _key: &u32) -> Self {
+///         Default::default()
+///     }
+/// }
+///
+/// #[derive(Properties, PartialEq, Clone)]
+/// struct RoomProps {
+///     room_id: u32,
+/// }
+///
+/// #[function_component]
+/// fn ViewRoom(&RoomProps { room_id }: &RoomProps) -> Html {
+///     let (room, dispatch) = use_store_keyed::<Room, _>(room_id);
+///     let onclick = dispatch.reduce_mut_callback(|room| room.messages.push("Hello".into()));
+///
+///     html! {
+///         <>
+///         <p>{ room.messages.len() }</p>
+///         <button {onclick}>{"Say hello"}</button>
+///         </>
+///     }
+/// }
+/// ```
+#[hook]
+pub fn use_store_keyed<S, K>(key: K) -> (Rc<S>, Dispatch<S>)
+where
+    S: KeyedStore<K>,
+    K: Hash + Eq + Clone + 'static,
+{
+    let cx = use_cx();
+    let update = use_force_update();
+    // Subscribed again if the context changes too, like when a new one is provided.
+    let dispatch = use_memo((cx, key), move |(cx, key)| {
+        Dispatch::<S>::keyed(cx, key.clone()).subscribe_silent(move |_| update.force_update())
+    });
+
+    (dispatch.get(), dispatch.deref().clone())
+}
+
+/// Provides the value seen through a [LensDispatch]. Only re-renders when that value changes.
+///
+/// # Example
+/// ```
+/// use yew::prelude::*;
+/// use yewdux::prelude::*;
+///
+/// #[derive(Properties, PartialEq, Clone)]
+/// struct CityProps {
+///     city: LensDispatch<String>,
+/// }
+///
+/// #[function_component]
+/// fn EditCity(props: &CityProps) -> Html {
+///     let city = use_lens_value(&props.city);
+///     let onclick = props.city.set_callback(|_| "Lisbon".to_string());
+///
+///     html! {
+///         <>
+///         <p>{ city.as_str() }</p>
+///         <button {onclick}>{"Move to Lisbon"}</button>
+///         </>
+///     }
+/// }
+/// ```
+#[hook]
+pub fn use_lens_value<C>(lens: &LensDispatch<C>) -> Rc<C>
+where
+    C: 'static,
+{
+    let update = use_force_update();
+    let lens = use_memo(lens.clone(), move |lens| {
+        lens.clone().subscribe_silent(move |_| update.force_update())
+    });
+
+    lens.get()
+}
+
 /// Simliar to ['use_store'], but only provides the state.
 #[hook]
 pub fn use_store_value<S>() -> Rc<S> 
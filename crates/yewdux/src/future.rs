@@ -0,0 +1,384 @@
+//! Asynchronous state changes.
+//!
+//! An [AsyncReducer] receives the current state, does some async work, and resolves to a
+//! [Reducer] that is applied when it completes.
+//!
+//! ```
+//! use std::rc::Rc;
+//!
+//! use yewdux::{future::AsyncReducer, prelude::*};
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Search {
+//!     results: Vec<String>,
+//! }
+//!
+//! struct Query(String);
+//!
+//! impl AsyncReducer<Search> for Query {
+//!     type Reducer = Box<dyn FnOnce(Rc<Search>) -> Rc<Search>>;
+//!
+//!     async fn apply(self, _state: Rc<Search>) -> Self::Reducer {
+//!         // Fetch results here.
+//!         let results = vec![self.0];
+//!         Box::new(move |_| Search { results }.into())
+//!     }
+//! }
+//! ```
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context as TaskContext, Poll, Waker},
+};
+
+use crate::{
+    store::{Reducer, Store},
+    Context,
+};
+
+/// A type that changes state asynchronously.
+///
+/// Implementors may use `async fn apply`.
+pub trait AsyncReducer<S>: 'static {
+    /// The reducer applied once this completes.
+    type Reducer: Reducer<S>;
+
+    /// Do some async work with current state, returning a reducer for the new state.
+    fn apply(self, state: Rc<S>) -> impl Future<Output = Self::Reducer>;
+}
+
+/// What to do when an [AsyncReducer] is applied while another of the same type is still running
+/// for the same store.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Run every call to completion.
+    #[default]
+    TakeEvery,
+    /// Cancel any running calls, so only the latest is applied.
+    TakeLatest,
+    /// Ignore new calls while one is running. Ignored calls are cancelled immediately.
+    DropWhileRunning,
+}
+
+/// Returned when awaiting a [FutureHandle] that was cancelled before its reducer was applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Future was cancelled")]
+pub struct Cancelled;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Running,
+    Applied,
+    Cancelled,
+}
+
+struct Task {
+    status: Cell<Status>,
+    /// Waker for whoever is awaiting the handle.
+    handle_waker: RefCell<Option<Waker>>,
+    /// Waker for the spawned future, so it may stop as soon as it's cancelled.
+    task_waker: RefCell<Option<Waker>>,
+}
+
+impl Task {
+    fn new(status: Status) -> Rc<Self> {
+        Rc::new(Self {
+            status: Cell::new(status),
+            handle_waker: Default::default(),
+            task_waker: Default::default(),
+        })
+    }
+
+    fn finish(&self, status: Status) {
+        if self.status.get() != Status::Running {
+            return;
+        }
+
+        self.status.set(status);
+        if let Some(waker) = self.handle_waker.take() {
+            waker.wake();
+        }
+        if let Some(waker) = self.task_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Handle to an [AsyncReducer] applied with
+/// [Dispatch::apply_future](crate::Dispatch::apply_future).
+///
+/// Await it to know when the resulting reducer has been applied, or [cancel](Self::cancel) it.
+/// Dropping the handle does **not** cancel the future.
+pub struct FutureHandle {
+    task: Rc<Task>,
+}
+
+impl std::fmt::Debug for FutureHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FutureHandle")
+            .field("status", &self.task.status.get())
+            .finish()
+    }
+}
+
+impl FutureHandle {
+    /// Stop the future. Its reducer will not be applied.
+    pub fn cancel(&self) {
+        self.task.finish(Status::Cancelled);
+    }
+
+    /// Whether the reducer has been applied, or the future was cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.task.status.get() != Status::Running
+    }
+}
+
+impl Future for FutureHandle {
+    type Output = Result<(), Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        match self.task.status.get() {
+            Status::Running => {
+                *self.task.handle_waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            Status::Applied => Poll::Ready(Ok(())),
+            Status::Cancelled => Poll::Ready(Err(Cancelled)),
+        }
+    }
+}
+
+/// Future that resolves to `None` as soon as its task is cancelled.
+struct Abortable<F> {
+    future: Pin<Box<F>>,
+    task: Rc<Task>,
+}
+
+impl<F: Future> Future for Abortable<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        if self.task.status.get() == Status::Cancelled {
+            return Poll::Ready(None);
+        }
+
+        *self.task.task_waker.borrow_mut() = Some(cx.waker().clone());
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
+
+/// Tasks currently running for store `S` and async reducer `R`.
+struct Running<S, R> {
+    tasks: Vec<Rc<Task>>,
+    _marker: std::marker::PhantomData<(S, R)>,
+}
+
+impl<S, R> Default for Running<S, R> {
+    fn default() -> Self {
+        Self {
+            tasks: Default::default(),
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Spawn an async reducer, applying its result when it completes.
+pub(crate) fn apply_future<S, R>(cx: &Context, reducer: R, overlap: Overlap) -> FutureHandle
+where
+    S: Store,
+    R: AsyncReducer<S>,
+{
+    let cx = cx.owner::<S>();
+    let running = cx.internal::<Running<S, R>>();
+    match overlap {
+        Overlap::TakeEvery => {}
+        Overlap::TakeLatest => {
+            let tasks = running.with_mut(|running| std::mem::take(&mut running.tasks));
+            for task in tasks {
+                task.finish(Status::Cancelled);
+            }
+        }
+        Overlap::DropWhileRunning => {
+            if !running.borrow().tasks.is_empty() {
+                return FutureHandle {
+                    task: Task::new(Status::Cancelled),
+                };
+            }
+        }
+    }
+
+    let task = Task::new(Status::Running);
+    running.with_mut(|running| running.tasks.push(Rc::clone(&task)));
+
+    let future = Abortable {
+        future: Box::pin(reducer.apply(cx.get::<S>())),
+        task: Rc::clone(&task),
+    };
+
+    yew::platform::spawn_local({
+        let cx = cx.clone();
+        let task = Rc::clone(&task);
+        async move {
+            if let Some(reducer) = future.await {
+                // Could have been cancelled by another task while we weren't polled.
+                if task.status.get() == Status::Running {
+                    cx.reduce(reducer);
+                    task.finish(Status::Applied);
+                }
+            }
+
+            cx.internal::<Running<S, R>>()
+                .with_mut(|running| running.tasks.retain(|x| !Rc::ptr_eq(x, &task)));
+        }
+    });
+
+    FutureHandle { task }
+}
+
+/// Run a test on a single threaded runtime.
+#[cfg(test)]
+pub(crate) fn run<F, Fut>(f: F)
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    let (tx, rx) = std::sync::mpsc::channel();
+    yew::platform::Runtime::default().spawn_pinned(move || async move {
+        f().await;
+        tx.send(()).unwrap();
+    });
+
+    rx.recv_timeout(std::time::Duration::from_secs(5))
+        .expect("test failed or timed out");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use yew::platform::pinned::oneshot;
+
+    use super::*;
+    use crate::Dispatch;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    type SetTo = Box<dyn FnOnce(Rc<TestState>) -> Rc<TestState>>;
+
+    /// Sets state to the value received, plus current state when the reducer was applied.
+    struct AddWhenReady(oneshot::Receiver<u32>);
+    impl AsyncReducer<TestState> for AddWhenReady {
+        type Reducer = SetTo;
+
+        async fn apply(self, state: Rc<TestState>) -> Self::Reducer {
+            let value = self.0.await.unwrap_or_default();
+            Box::new(move |_| TestState(state.0 + value).into())
+        }
+    }
+
+    #[test]
+    fn applies_reducer_when_resolved() {
+        run(|| async {
+            let dispatch = Dispatch::<TestState>::new(&Context::new());
+            let (tx, rx) = oneshot::channel();
+
+            let handle = dispatch.apply_future(AddWhenReady(rx));
+            assert_eq!(dispatch.get().0, 0);
+
+            tx.send(1).unwrap();
+            assert_eq!(handle.await, Ok(()));
+            assert_eq!(dispatch.get().0, 1);
+        });
+    }
+
+    #[test]
+    fn cancelled_reducer_is_not_applied() {
+        run(|| async {
+            let dispatch = Dispatch::<TestState>::new(&Context::new());
+            let (tx, rx) = oneshot::channel();
+
+            let handle = dispatch.apply_future(AddWhenReady(rx));
+            handle.cancel();
+            let _ = tx.send(1);
+
+            assert!(handle.is_finished());
+            assert_eq!(handle.await, Err(Cancelled));
+            yew::platform::time::sleep(Duration::from_millis(1)).await;
+            assert_eq!(dispatch.get().0, 0);
+        });
+    }
+
+    #[test]
+    fn take_every_applies_all() {
+        run(|| async {
+            let dispatch = Dispatch::<TestState>::new(&Context::new());
+            let (tx1, rx1) = oneshot::channel();
+            let (tx2, rx2) = oneshot::channel();
+
+            let first = dispatch.apply_future_with(AddWhenReady(rx1), Overlap::TakeEvery);
+            let second = dispatch.apply_future_with(AddWhenReady(rx2), Overlap::TakeEvery);
+
+            tx1.send(1).unwrap();
+            tx2.send(2).unwrap();
+
+            assert_eq!(first.await, Ok(()));
+            assert_eq!(second.await, Ok(()));
+        });
+    }
+
+    #[test]
+    fn take_latest_cancels_running() {
+        run(|| async {
+            let dispatch = Dispatch::<TestState>::new(&Context::new());
+            let (tx1, rx1) = oneshot::channel();
+            let (tx2, rx2) = oneshot::channel();
+
+            let first = dispatch.apply_future_with(AddWhenReady(rx1), Overlap::TakeLatest);
+            let second = dispatch.apply_future_with(AddWhenReady(rx2), Overlap::TakeLatest);
+
+            let _ = tx1.send(1);
+            tx2.send(2).unwrap();
+
+            assert_eq!(first.await, Err(Cancelled));
+            assert_eq!(second.await, Ok(()));
+            assert_eq!(dispatch.get().0, 2);
+        });
+    }
+
+    #[test]
+    fn drop_while_running_ignores_new_calls() {
+        run(|| async {
+            let dispatch = Dispatch::<TestState>::new(&Context::new());
+            let (tx1, rx1) = oneshot::channel();
+            let (tx2, rx2) = oneshot::channel();
+
+            let first = dispatch.apply_future_with(AddWhenReady(rx1), Overlap::DropWhileRunning);
+            let second = dispatch.apply_future_with(AddWhenReady(rx2), Overlap::DropWhileRunning);
+            assert_eq!(second.await, Err(Cancelled));
+
+            tx1.send(1).unwrap();
+            let _ = tx2.send(2);
+            assert_eq!(first.await, Ok(()));
+            assert_eq!(dispatch.get().0, 1);
+
+            // Calls are accepted again once nothing is running.
+            let (tx3, rx3) = oneshot::channel();
+            let third = dispatch.apply_future_with(AddWhenReady(rx3), Overlap::DropWhileRunning);
+            tx3.send(1).unwrap();
+            assert_eq!(third.await, Ok(()));
+            assert_eq!(dispatch.get().0, 2);
+        });
+    }
+}
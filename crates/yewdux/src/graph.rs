@@ -0,0 +1,460 @@
+//! Dependencies between derived stores and their sources.
+//!
+//! When a store changes, every store derived from it is recomputed once, in topological order,
+//! before any subscriber is notified. So subscribers never see a derived store that is
+//! inconsistent with its sources, even with diamond-shaped dependencies.
+use std::{
+    any::{type_name, TypeId},
+    collections::{HashMap, HashSet},
+};
+
+use crate::{context::WeakContext, store::Store, Context};
+
+/// Recomputes a derived store from its sources, returning whether it changed.
+pub(crate) type Recompute = fn(&Context) -> bool;
+
+/// Sends the latest state of a store to its subscribers.
+pub(crate) type Notify = fn(&Context);
+
+/// A source store of a derived store.
+#[derive(Clone, Copy)]
+pub struct Source {
+    id: TypeId,
+    name: &'static str,
+    /// Context the source is read from, for a derived store owned by the given context.
+    owner: fn(&Context) -> &Context,
+}
+
+impl Source {
+    pub(crate) fn of<S: Store>() -> Self {
+        Self {
+            id: TypeId::of::<S>(),
+            name: type_name::<S>(),
+            owner: Context::owner::<S>,
+        }
+    }
+
+    /// Whether this is `id` in `cx`, as seen from a derived store owned by `derived`.
+    fn is(&self, id: TypeId, cx: &Context, derived: &Context) -> bool {
+        self.id == id && (self.owner)(derived).ptr_eq(cx)
+    }
+}
+
+struct Relation {
+    /// Identifies the relation, so it's only added once.
+    id: TypeId,
+    sources: Vec<Source>,
+    recompute: Recompute,
+}
+
+struct Node {
+    derived: TypeId,
+    /// Context that owns the derived store. Its sources are read from here.
+    cx: WeakContext,
+    name: &'static str,
+    relations: Vec<Relation>,
+    /// Notifies subscribers as if the store was changed directly.
+    deliver: Notify,
+}
+
+/// Derived stores in a context tree. Shared by every context with the same root, as a store
+/// provided in a child may derive from one in its parents.
+#[derive(Default)]
+pub(crate) struct Graph {
+    nodes: Vec<Node>,
+    names: HashMap<TypeId, &'static str>,
+}
+
+impl Graph {
+    /// Types of derived stores that depend directly on `source`, in any context.
+    fn dependents(&self, source: TypeId) -> impl Iterator<Item = TypeId> + '_ {
+        self.nodes
+            .iter()
+            .filter(move |node| {
+                node.relations
+                    .iter()
+                    .any(|relation| relation.sources.iter().any(|s| s.id == source))
+            })
+            .map(|node| node.derived)
+    }
+
+    /// Derived stores that depend directly on `source` in `cx`, with their owning context.
+    fn dependents_in<'a>(
+        &'a self,
+        source: TypeId,
+        cx: &'a Context,
+    ) -> impl Iterator<Item = (usize, Context)> + 'a {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, node)| {
+                let derived = node.cx.upgrade()?;
+                node.relations
+                    .iter()
+                    .any(|relation| relation.sources.iter().any(|s| s.is(source, cx, &derived)))
+                    .then_some((index, derived))
+            })
+    }
+
+    /// Path of dependencies from `from` to `to`, if there is one.
+    fn path(&self, from: TypeId, to: TypeId) -> Option<Vec<TypeId>> {
+        if from == to {
+            return Some(vec![from]);
+        }
+
+        self.dependents(from).find_map(|next| {
+            let mut path = self.path(next, to)?;
+            path.insert(0, from);
+            Some(path)
+        })
+    }
+
+    fn name(&self, id: TypeId) -> &'static str {
+        self.names.get(&id).copied().unwrap_or("<unknown>")
+    }
+
+    /// Derived stores reachable from `changed` in `cx`, in topological order.
+    fn order(&self, cx: &Context, changed: &[TypeId]) -> Vec<(usize, Context)> {
+        fn visit(
+            graph: &Graph,
+            id: TypeId,
+            cx: &Context,
+            seen: &mut HashSet<usize>,
+            order: &mut Vec<(usize, Context)>,
+        ) {
+            for (next, derived) in graph.dependents_in(id, cx) {
+                if seen.insert(next) {
+                    visit(graph, graph.nodes[next].derived, &derived, seen, order);
+                    order.push((next, derived));
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut order = Vec::new();
+        for id in changed {
+            visit(self, *id, cx, &mut seen, &mut order);
+        }
+
+        order.reverse();
+        order
+    }
+}
+
+/// Make `D` derived from `sources`, recomputed with `recompute` when any of them changes.
+///
+/// The relation belongs to the context that owns `D`, and sources are read as seen from there.
+///
+/// # Panics
+///
+/// Panics if this would make `D` depend on itself.
+pub(crate) fn add<D: Store>(cx: &Context, id: TypeId, sources: &[Source], recompute: Recompute) {
+    let derived = TypeId::of::<D>();
+    let owner = cx.owner::<D>();
+    cx.root().internal::<Graph>().with_mut(|graph| {
+        graph.names.insert(derived, type_name::<D>());
+        graph
+            .names
+            .extend(sources.iter().map(|source| (source.id, source.name)));
+
+        for source in sources {
+            if let Some(path) = graph.path(derived, source.id) {
+                let cycle = path
+                    .iter()
+                    .chain(Some(&derived))
+                    .map(|id| graph.name(*id))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                panic!("Cycle in derived stores: {cycle}");
+            }
+        }
+
+        // Good time to forget stores of dropped contexts.
+        graph.nodes.retain(|node| node.cx.upgrade().is_some());
+        let index = match graph.nodes.iter().position(|node| {
+            node.derived == derived && node.cx.upgrade().is_some_and(|cx| cx.ptr_eq(owner))
+        }) {
+            Some(index) => index,
+            None => {
+                graph.nodes.push(Node {
+                    derived,
+                    cx: owner.downgrade(),
+                    name: type_name::<D>(),
+                    relations: Default::default(),
+                    deliver: deliver::<D>,
+                });
+                graph.nodes.len() - 1
+            }
+        };
+
+        let node = &mut graph.nodes[index];
+        if node.relations.iter().any(|relation| relation.id == id) {
+            return;
+        }
+
+        node.relations.push(Relation {
+            id,
+            sources: sources.to_vec(),
+            recompute,
+        });
+    });
+}
+
+fn deliver<S: Store>(cx: &Context) {
+    cx.deliver::<S>(cx.get::<S>());
+}
+
+/// Recompute every store derived from `changed` in `cx`, in topological order. Stores are only
+/// recomputed when one of their sources changed. Returns the stores that changed, with their
+/// owning context and how to notify their subscribers.
+pub(crate) fn update(cx: &Context, changed: &[TypeId]) -> Vec<(TypeId, Context, Notify)> {
+    let graph = cx.root().internal::<Graph>();
+    // Collected first, so the graph isn't borrowed while stores are recomputed.
+    let steps = {
+        let graph = graph.borrow();
+        if graph.nodes.is_empty() {
+            return Vec::new();
+        }
+
+        graph
+            .order(cx, changed)
+            .into_iter()
+            .map(|(index, derived)| {
+                let node = &graph.nodes[index];
+                let relations = node
+                    .relations
+                    .iter()
+                    .map(|relation| (relation.sources.clone(), relation.recompute))
+                    .collect::<Vec<_>>();
+                (node.derived, derived, node.name, relations, node.deliver)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut changed = changed
+        .iter()
+        .map(|id| (*id, cx.clone()))
+        .collect::<Vec<_>>();
+    let mut updated = Vec::new();
+    for (id, derived, name, relations, notify) in steps {
+        let mut did_change = false;
+        for (sources, recompute) in relations {
+            let source_changed = sources
+                .iter()
+                .any(|source| changed.iter().any(|(id, cx)| source.is(*id, cx, &derived)));
+            if source_changed {
+                did_change |= recompute(&derived);
+            }
+        }
+
+        if did_change {
+            crate::log::trace!("Recomputed derived store {name}");
+            changed.push((id, derived.clone()));
+            updated.push((id, derived, notify));
+        }
+    }
+
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{derived_from::DerivedFrom, derived_from::DerivedFromMany, mrc::Mrc, Dispatch};
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Source(u32);
+    impl Store for Source {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    /// Derived from `Source`.
+    #[derive(Clone, PartialEq, Eq)]
+    struct Doubled(u32);
+    impl Store for Doubled {
+        fn new(cx: &Context) -> Self {
+            cx.derived_from::<Source, Self>();
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    impl DerivedFrom<Source> for Doubled {
+        fn on_change(&self, state: Rc<Source>) -> Self {
+            Self(state.0 * 2)
+        }
+    }
+
+    /// Derived from `Source` and `Doubled`, recording every pair of values it saw.
+    #[derive(Clone, PartialEq, Eq)]
+    struct Sum {
+        seen: Vec<(u32, u32)>,
+    }
+    impl Store for Sum {
+        fn new(cx: &Context) -> Self {
+            cx.derived_from_many::<(Source, Doubled), Self>();
+            Self { seen: Vec::new() }
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    impl DerivedFromMany<(Source, Doubled)> for Sum {
+        fn on_change(&self, (source, doubled): (Rc<Source>, Rc<Doubled>)) -> Self {
+            let mut seen = self.seen.clone();
+            seen.push((source.0, doubled.0));
+            Self { seen }
+        }
+    }
+
+    #[test]
+    fn diamond_is_recomputed_once_with_consistent_sources() {
+        let cx = Context::new();
+        // Initialized in the order that used to cause glitches.
+        cx.get::<Sum>();
+        cx.get::<Doubled>();
+
+        cx.set(Source(1));
+
+        assert_eq!(cx.get::<Sum>().seen, vec![(1, 2)]);
+    }
+
+    #[test]
+    fn derived_stores_are_updated_before_subscribers_are_notified() {
+        let cx = Context::new();
+        cx.get::<Sum>();
+        cx.get::<Doubled>();
+        let seen = Mrc::new(Vec::new());
+        let _id = {
+            let (cx, seen) = (cx.clone(), seen.clone());
+            Dispatch::<Source>::new(&cx.clone()).subscribe_silent(move |source: Rc<Source>| {
+                seen.borrow_mut().push((source.0, cx.get::<Doubled>().0))
+            })
+        };
+
+        cx.set(Source(1));
+
+        assert_eq!(*seen.borrow(), vec![(1, 2)]);
+    }
+
+    #[test]
+    fn batch_recomputes_once() {
+        let cx = Context::new();
+        cx.get::<Sum>();
+        cx.get::<Doubled>();
+
+        cx.batch(|cx| {
+            cx.set(Source(1));
+            cx.set(Source(2));
+        });
+
+        assert_eq!(cx.get::<Sum>().seen, vec![(2, 4)]);
+    }
+
+    #[test]
+    fn child_source_does_not_change_parent_derived_store() {
+        let root = Context::new();
+        root.get::<Doubled>();
+        let child = root.child();
+        child.provide(Source(0));
+
+        child.set(Source(5));
+
+        assert_eq!(root.get::<Source>().0, 0);
+        assert_eq!(root.get::<Doubled>().0, 0);
+    }
+
+    #[test]
+    fn parent_source_changes_derived_store_provided_in_child() {
+        let root = Context::new();
+        let child = root.child();
+        child.provide(Doubled(0));
+        child.derived_from::<Source, Doubled>();
+        let seen = Mrc::new(Vec::new());
+        let _id = {
+            let seen = seen.clone();
+            Dispatch::<Doubled>::new(&child)
+                .subscribe_silent(move |doubled: Rc<Doubled>| seen.borrow_mut().push(doubled.0))
+        };
+
+        root.set(Source(2));
+        root.batch(|cx| cx.set(Source(3)));
+
+        assert_eq!(child.get::<Doubled>().0, 6);
+        assert_eq!(*seen.borrow(), vec![4, 6]);
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Ping(u32);
+    impl Store for Ping {
+        fn new(cx: &Context) -> Self {
+            cx.derived_from::<Pong, Self>();
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    impl DerivedFrom<Pong> for Ping {
+        fn on_change(&self, state: Rc<Pong>) -> Self {
+            Self(state.0)
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Pong(u32);
+    impl Store for Pong {
+        fn new(cx: &Context) -> Self {
+            cx.derived_from::<Ping, Self>();
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    impl DerivedFrom<Ping> for Pong {
+        fn on_change(&self, state: Rc<Ping>) -> Self {
+            Self(state.0)
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cycle in derived stores")]
+    fn cycles_are_detected() {
+        let cx = Context::new();
+        cx.get::<Ping>();
+        cx.get::<Pong>();
+    }
+
+    #[test]
+    fn cycle_is_reported_with_type_names() {
+        let cx = Context::new();
+        cx.get::<Ping>();
+
+        let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cx.get::<Pong>();
+        }))
+        .unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+
+        assert!(message.contains(type_name::<Ping>()));
+        assert!(message.contains(type_name::<Pong>()));
+    }
+}
@@ -0,0 +1,221 @@
+//! Carrying state over from server side rendering to the client.
+//!
+//! Stores opt in with `#[store(hydrate)]`, which needs them to implement [Serialize] and
+//! [DeserializeOwned]. After rendering on the server, [to_script] serializes every hydratable store
+//! initialized in the context into a `<script>` element, to embed in the page. On the client,
+//! [YewduxRoot](crate::YewduxRoot) loads it into its context before the first render, so stores
+//! start with the state the server rendered instead of calling [Store::new] again.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use yewdux::{hydrate, prelude::*};
+//!
+//! #[derive(Default, Clone, PartialEq, Serialize, Deserialize, Store)]
+//! #[store(hydrate)]
+//! struct Posts(Vec<String>);
+//!
+//! // On the server, after rendering with `cx`.
+//! let server = yewdux::Context::new();
+//! server.set(Posts(vec!["Hello".to_string()]));
+//! let script = hydrate::to_script(&server).unwrap();
+//!
+//! // On the client, this is done by `YewduxRoot`.
+//! let client = yewdux::Context::new();
+//! let payload = script
+//!     .strip_prefix(r#"<script type="application/json" id="yewdux-state">"#)
+//!     .and_then(|script| script.strip_suffix("</script>"))
+//!     .unwrap();
+//! hydrate::load(&client, payload).unwrap();
+//!
+//! assert_eq!(client.get::<Posts>().0, vec!["Hello".to_string()]);
+//! ```
+//!
+//! Stores are identified by their type name, so server and client must be built from the same
+//! code. Only stores in the given context are included, not those provided in its children.
+use std::{any::type_name, collections::BTreeMap, collections::HashMap};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{store::Store, Context};
+
+/// Id of the `<script>` element holding the serialized state.
+pub const SCRIPT_ID: &str = "yewdux-state";
+
+#[derive(Debug, thiserror::Error)]
+pub enum HydrateError {
+    #[error("Window not found")]
+    WindowNotFound,
+    #[error("Element #{SCRIPT_ID} not found")]
+    PayloadNotFound,
+    #[error("A serde error occurred")]
+    Serde(#[from] serde_json::Error),
+}
+
+type Save = fn(&Context) -> Result<Value, serde_json::Error>;
+type Load = fn(&Context, Value) -> Result<(), serde_json::Error>;
+
+#[derive(Default)]
+struct Hydration {
+    /// Hydratable stores initialized in this context, by type name.
+    stores: BTreeMap<&'static str, (Save, Load)>,
+    /// Loaded state of stores that aren't initialized yet.
+    pending: HashMap<String, Value>,
+}
+
+/// Register `S` as hydratable, returning its loaded state if there is any.
+///
+/// Called by `#[store(hydrate)]`. Stores implementing [Store] manually may call it in
+/// [Store::new], using the returned state when there is one.
+pub fn init<S>(cx: &Context) -> Option<S>
+where
+    S: Store + Serialize + DeserializeOwned,
+{
+    let key = type_name::<S>();
+    let value = cx.internal::<Hydration>().with_mut(|hydration| {
+        hydration.stores.insert(key, (save::<S>, load_store::<S>));
+        hydration.pending.remove(key)
+    })?;
+
+    match serde_json::from_value(value) {
+        Ok(state) => Some(state),
+        Err(err) => {
+            crate::log::error!("Unable to hydrate {}: {:?}", key, err);
+            None
+        }
+    }
+}
+
+fn save<S: Store + Serialize>(cx: &Context) -> Result<Value, serde_json::Error> {
+    serde_json::to_value(&*cx.get::<S>())
+}
+
+fn load_store<S: Store + DeserializeOwned>(
+    cx: &Context,
+    value: Value,
+) -> Result<(), serde_json::Error> {
+    cx.set(serde_json::from_value::<S>(value)?);
+    Ok(())
+}
+
+/// Serialize every hydratable store initialized in `cx`, as a JSON object.
+pub fn serialize(cx: &Context) -> Result<String, HydrateError> {
+    // Collected first, as reading a store may initialize others.
+    let stores = cx.internal::<Hydration>().borrow().stores.clone();
+    let state = stores
+        .into_iter()
+        .map(|(key, (save, _))| Ok((key.to_string(), save(cx)?)))
+        .collect::<Result<serde_json::Map<_, _>, serde_json::Error>>()?;
+
+    Ok(serde_json::to_string(&state)?)
+}
+
+/// Serialize every hydratable store initialized in `cx` into a `<script>` element, to embed in
+/// server rendered html.
+pub fn to_script(cx: &Context) -> Result<String, HydrateError> {
+    // `<` only appears in strings, where it can be escaped, so the payload can't close the element.
+    let payload = serialize(cx)?.replace('<', "\\u003c");
+
+    Ok(format!(
+        r#"<script type="application/json" id="{SCRIPT_ID}">{payload}</script>"#
+    ))
+}
+
+/// Load state serialized with [serialize] into `cx`. Stores that are already initialized are set
+/// immediately, and others start with the loaded state once initialized.
+pub fn load(cx: &Context, payload: &str) -> Result<(), HydrateError> {
+    let state = serde_json::from_str::<HashMap<String, Value>>(payload)?;
+    let initialized = cx.internal::<Hydration>().with_mut(|hydration| {
+        let mut initialized = Vec::new();
+        for (key, value) in state {
+            match hydration.stores.get(key.as_str()) {
+                Some((_, load)) => initialized.push((*load, value)),
+                None => {
+                    hydration.pending.insert(key, value);
+                }
+            }
+        }
+
+        initialized
+    });
+
+    // Set outside the borrow, as it notifies subscribers.
+    for (load, value) in initialized {
+        load(cx, value)?;
+    }
+
+    Ok(())
+}
+
+/// Load state embedded in the page by [to_script] into `cx`.
+#[cfg(target_arch = "wasm32")]
+pub fn hydrate(cx: &Context) -> Result<(), HydrateError> {
+    load(cx, &page_payload()?)
+}
+
+/// State embedded in the page by [to_script].
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn page_payload() -> Result<String, HydrateError> {
+    web_sys::window()
+        .ok_or(HydrateError::WindowNotFound)?
+        .document()
+        .ok_or(HydrateError::WindowNotFound)?
+        .get_element_by_id(SCRIPT_ID)
+        .and_then(|element| element.text_content())
+        .ok_or(HydrateError::PayloadNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+    struct TestState(String);
+    impl Store for TestState {
+        fn new(cx: &Context) -> Self {
+            init(cx).unwrap_or_else(|| Self("new".to_string()))
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[test]
+    fn loaded_state_is_used_on_init() {
+        let server = Context::new();
+        server.set(TestState("server".to_string()));
+        let payload = serialize(&server).unwrap();
+
+        let client = Context::new();
+        load(&client, &payload).unwrap();
+
+        assert_eq!(client.get::<TestState>().0, "server");
+    }
+
+    #[test]
+    fn loaded_state_is_set_if_initialized() {
+        let server = Context::new();
+        server.set(TestState("server".to_string()));
+        let payload = serialize(&server).unwrap();
+
+        let client = Context::new();
+        client.get::<TestState>();
+        load(&client, &payload).unwrap();
+
+        assert_eq!(client.get::<TestState>().0, "server");
+    }
+
+    #[test]
+    fn script_cannot_be_closed_by_state() {
+        let cx = Context::new();
+        cx.set(TestState("</script><script>alert(1)".to_string()));
+
+        let script = to_script(&cx).unwrap();
+
+        assert_eq!(script.matches("</script>").count(), 1);
+        assert!(serialize(&cx).unwrap().contains("</script>"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Inspecting the stores in a [Context].
+//!
+//! Useful for debug panels, finding subscriptions that are never dropped, and test assertions.
+//!
+//! ```
+//! use yewdux::prelude::*;
+//!
+//! #[derive(Default, Clone, PartialEq, Store, serde::Serialize)]
+//! struct Counter(u32);
+//!
+//! let cx = yewdux::Context::new();
+//! cx.enable_snapshot::<Counter>();
+//!
+//! let dispatch = Dispatch::<Counter>::new(&cx);
+//! dispatch.set(Counter(1));
+//!
+//! let info = cx.store_info::<Counter>().unwrap();
+//! assert_eq!(info.subscriber_count, 0);
+//! assert_eq!(info.last_changed, Some(cx.revision()));
+//! assert_eq!(info.snapshot, Some(serde_json::json!(1)));
+//! ```
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+};
+
+use serde::Serialize;
+
+use crate::{listener, store::Store, Context};
+
+/// Information about a store in a [Context].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreInfo {
+    pub type_name: &'static str,
+    /// Number of subscribers, not counting listeners.
+    pub subscriber_count: usize,
+    /// Type names of the [Listener](crate::Listener)s attached to this store.
+    pub listeners: Vec<&'static str>,
+    /// [Revision](Context::revision) of the last change subscribers were notified of, or `None` if
+    /// the store hasn't changed since it was initialized.
+    pub last_changed: Option<u64>,
+    /// Current state, if enabled with [Context::enable_snapshot].
+    pub snapshot: Option<serde_json::Value>,
+}
+
+type SubscriberCount = fn(&Context) -> usize;
+type Snapshot = fn(&Context) -> Option<serde_json::Value>;
+
+#[derive(Clone, Copy)]
+struct Record {
+    type_name: &'static str,
+    /// Order of initialization.
+    order: u64,
+    subscriber_count: SubscriberCount,
+    last_changed: Option<u64>,
+}
+
+/// Stores initialized in a context. Stores used internally are never registered.
+#[derive(Default)]
+pub(crate) struct Registry {
+    records: HashMap<TypeId, Record>,
+    snapshots: HashMap<TypeId, Snapshot>,
+    initialized: u64,
+    revision: u64,
+}
+
+impl Registry {
+    pub(crate) fn register<S: Store>(&mut self) {
+        self.initialized += 1;
+        self.records.insert(
+            TypeId::of::<S>(),
+            Record {
+                type_name: type_name::<S>(),
+                order: self.initialized,
+                subscriber_count: subscriber_count::<S>,
+                last_changed: None,
+            },
+        );
+    }
+
+    pub(crate) fn unregister<S: Store>(&mut self) {
+        self.records.remove(&TypeId::of::<S>());
+    }
+
+    pub(crate) fn changed<S: Store>(&mut self) {
+        if let Some(record) = self.records.get_mut(&TypeId::of::<S>()) {
+            self.revision += 1;
+            record.last_changed = Some(self.revision);
+        }
+    }
+
+    pub(crate) fn enable_snapshot<S: Store + Serialize>(&mut self) {
+        self.snapshots.insert(TypeId::of::<S>(), snapshot::<S>);
+    }
+
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision
+    }
+}
+
+fn subscriber_count<S: Store>(cx: &Context) -> usize {
+    cx.subscriber_count::<S>()
+}
+
+fn snapshot<S: Store + Serialize>(cx: &Context) -> Option<serde_json::Value> {
+    match serde_json::to_value(cx.get::<S>().as_ref()) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            crate::log::error!("Unable to serialize {}: {:?}", type_name::<S>(), err);
+            None
+        }
+    }
+}
+
+/// Information about all stores in `cx`, in the order they were initialized.
+pub(crate) fn stores(cx: &Context) -> Vec<StoreInfo> {
+    // Collected first, so the registry isn't borrowed while stores are accessed.
+    let mut records = cx.internal::<Registry>().with_mut(|registry| {
+        registry
+            .records
+            .iter()
+            .map(|(type_id, record)| {
+                let snapshot = registry.snapshots.get(type_id).copied();
+                (*type_id, *record, snapshot)
+            })
+            .collect::<Vec<_>>()
+    });
+    records.sort_by_key(|(_, record, _)| record.order);
+
+    records
+        .into_iter()
+        .map(|(type_id, record, snapshot)| StoreInfo {
+            type_name: record.type_name,
+            subscriber_count: (record.subscriber_count)(cx),
+            listeners: listener::listener_names(cx, type_id),
+            last_changed: record.last_changed,
+            snapshot: snapshot.and_then(|snapshot| snapshot(cx)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::{Dispatch, Listener};
+
+    #[derive(Clone, PartialEq, Eq, Serialize)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState2(u32);
+    impl Store for TestState2 {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    struct TestListener;
+    impl Listener for TestListener {
+        type Store = TestState;
+
+        fn on_change(&self, _cx: &Context, _state: Rc<Self::Store>) {}
+    }
+
+    fn type_names(cx: &Context) -> Vec<&'static str> {
+        cx.stores().map(|info| info.type_name).collect()
+    }
+
+    #[test]
+    fn lists_stores_in_order_of_initialization() {
+        let cx = Context::new();
+        assert!(type_names(&cx).is_empty());
+
+        cx.get::<TestState2>();
+        let _id = Dispatch::<TestState>::new(&cx).subscribe(|_| {});
+        cx.init_listener(|| TestListener);
+
+        assert_eq!(
+            type_names(&cx),
+            vec![type_name::<TestState2>(), type_name::<TestState>()]
+        );
+    }
+
+    #[test]
+    fn counts_subscribers_and_listeners() {
+        let cx = Context::new();
+        cx.init_listener(|| TestListener);
+        let id = Dispatch::<TestState>::new(&cx).subscribe(|_| {});
+        let _id = Dispatch::<TestState>::new(&cx).subscribe(|_| {});
+
+        let info = cx.store_info::<TestState>().unwrap();
+        assert_eq!(info.subscriber_count, 2);
+        assert_eq!(info.listeners, vec![type_name::<TestListener>()]);
+
+        drop(id);
+        assert_eq!(cx.store_info::<TestState>().unwrap().subscriber_count, 1);
+    }
+
+    #[test]
+    fn tracks_last_change() {
+        let cx = Context::new();
+        cx.get::<TestState>();
+        cx.get::<TestState2>();
+        assert_eq!(cx.store_info::<TestState>().unwrap().last_changed, None);
+
+        cx.set(TestState(1));
+        let changed = cx.revision();
+        cx.set(TestState2(1));
+        // Not a change, because subscribers aren't notified.
+        cx.set(TestState(1));
+
+        assert_eq!(
+            cx.store_info::<TestState>().unwrap().last_changed,
+            Some(changed)
+        );
+        assert_eq!(
+            cx.store_info::<TestState2>().unwrap().last_changed,
+            Some(cx.revision())
+        );
+    }
+
+    #[test]
+    fn snapshot_is_opt_in() {
+        let cx = Context::new();
+        cx.set(TestState(1));
+        assert_eq!(cx.store_info::<TestState>().unwrap().snapshot, None);
+
+        cx.enable_snapshot::<TestState>();
+        assert_eq!(
+            cx.store_info::<TestState>().unwrap().snapshot,
+            Some(serde_json::json!(1))
+        );
+    }
+
+    #[test]
+    fn removed_stores_are_not_listed() {
+        let cx = Context::new();
+        cx.get::<TestState>();
+
+        cx.remove::<TestState>();
+
+        assert!(cx.store_info::<TestState>().is_none());
+        assert!(type_names(&cx).is_empty());
+    }
+}
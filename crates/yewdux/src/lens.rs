@@ -0,0 +1,309 @@
+//! Using part of a store as if it were its own store.
+//!
+//! A [Lens] reads a child value out of a parent store, and writes it back. With
+//! [Dispatch::lens](crate::Dispatch::lens) it makes a [LensDispatch], which works like a
+//! [Dispatch](crate::Dispatch) for the child alone. So reusable components can take a
+//! `LensDispatch<Address>` without knowing which store the address lives in.
+//!
+//! ```
+//! use std::rc::Rc;
+//!
+//! use yewdux::{lens::LensDispatch, prelude::*};
+//!
+//! #[derive(Default, Clone, PartialEq)]
+//! struct Address {
+//!     city: String,
+//! }
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct UserProfile {
+//!     name: String,
+//!     address: Address,
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! let address: LensDispatch<Address> = Dispatch::<UserProfile>::new(&cx).lens((
+//!     |profile: &UserProfile| profile.address.clone(),
+//!     |profile: &mut UserProfile, address| profile.address = address,
+//! ));
+//!
+//! address.reduce_mut(|address| address.city = "Lisbon".to_string());
+//! assert_eq!(cx.get::<UserProfile>().address.city, "Lisbon");
+//! ```
+use std::{any::Any, rc::Rc};
+
+use yew::Callback;
+
+use crate::{store::Store, subscriber::Callable, Context};
+
+/// Projects a `Child` out of a `Parent`, and writes it back.
+///
+/// Implemented for pairs of functions `(get, set)`.
+pub trait Lens<Parent, Child>: 'static {
+    /// Read the child from `parent`.
+    fn get(&self, parent: &Parent) -> Child;
+    /// Write `child` into `parent`.
+    fn set(&self, parent: &mut Parent, child: Child);
+}
+
+impl<Parent, Child, G, S> Lens<Parent, Child> for (G, S)
+where
+    G: Fn(&Parent) -> Child + 'static,
+    S: Fn(&mut Parent, Child) + 'static,
+{
+    fn get(&self, parent: &Parent) -> Child {
+        (self.0)(parent)
+    }
+
+    fn set(&self, parent: &mut Parent, child: Child) {
+        (self.1)(parent, child)
+    }
+}
+
+/// A store seen through a lens, with its type erased.
+trait Target<C> {
+    fn get(&self) -> C;
+    fn reduce_mut(&self, f: &mut dyn FnMut(&mut C));
+    fn subscribe(&self, on_change: Box<dyn Fn(Rc<C>)>) -> Rc<dyn Any>;
+}
+
+struct Lensed<P, L> {
+    cx: Context,
+    lens: Rc<L>,
+    _marker: std::marker::PhantomData<P>,
+}
+
+impl<P, C, L> Target<C> for Lensed<P, L>
+where
+    P: Store + Clone,
+    C: PartialEq + 'static,
+    L: Lens<P, C>,
+{
+    fn get(&self) -> C {
+        self.lens.get(&self.cx.get::<P>())
+    }
+
+    fn reduce_mut(&self, f: &mut dyn FnMut(&mut C)) {
+        self.cx.reduce_mut(|parent: &mut P| {
+            let mut child = self.lens.get(parent);
+            f(&mut child);
+            self.lens.set(parent, child);
+        });
+    }
+
+    fn subscribe(&self, on_change: Box<dyn Fn(Rc<C>)>) -> Rc<dyn Any> {
+        let lens = self.lens.clone();
+        let selection = self
+            .cx
+            .select(move |parent: &P| lens.get(parent), on_change);
+
+        Rc::new(selection)
+    }
+}
+
+/// Like a [Dispatch](crate::Dispatch) for part of a store, made with
+/// [Dispatch::lens](crate::Dispatch::lens). Changes are written back to the parent store.
+///
+/// Subscribers are notified only when the part seen through the lens changes.
+pub struct LensDispatch<C> {
+    target: Rc<dyn Target<C>>,
+    _subscription: Option<Rc<dyn Any>>,
+}
+
+impl<C> std::fmt::Debug for LensDispatch<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LensDispatch")
+            .field("subscribed", &self._subscription.is_some())
+            .finish()
+    }
+}
+
+impl<C: 'static> LensDispatch<C> {
+    pub(crate) fn new<P, L>(cx: &Context, lens: L) -> Self
+    where
+        P: Store + Clone,
+        C: PartialEq,
+        L: Lens<P, C>,
+    {
+        Self {
+            target: Rc::new(Lensed {
+                cx: cx.clone(),
+                lens: Rc::new(lens),
+                _marker: Default::default(),
+            }),
+            _subscription: None,
+        }
+    }
+
+    /// Create a lens dispatch that subscribes to changes in the child. The current value is sent
+    /// immediately. Automatically unsubscribes when this dispatch is dropped.
+    pub fn subscribe<F: Callable<C>>(self, on_change: F) -> Self {
+        on_change.call(self.get());
+        self.subscribe_silent(on_change)
+    }
+
+    /// Similar to [Self::subscribe], however the current value is **not** sent immediately.
+    pub fn subscribe_silent<F: Callable<C>>(self, on_change: F) -> Self {
+        let subscription = self
+            .target
+            .subscribe(Box::new(move |child| on_change.call(child)));
+
+        Self {
+            target: self.target,
+            _subscription: Some(subscription),
+        }
+    }
+
+    /// Get the current value of the child.
+    pub fn get(&self) -> Rc<C> {
+        Rc::new(self.target.get())
+    }
+
+    /// Set the child to given value.
+    pub fn set(&self, val: C) {
+        let mut val = Some(val);
+        self.target.reduce_mut(&mut |child| {
+            if let Some(val) = val.take() {
+                *child = val;
+            }
+        });
+    }
+
+    /// Set the child using value from callback.
+    pub fn set_callback<E, F>(&self, f: F) -> Callback<E>
+    where
+        F: Fn(E) -> C + 'static,
+    {
+        let dispatch = self.clone();
+        Callback::from(move |e| dispatch.set(f(e)))
+    }
+
+    /// Mutate the child with given function.
+    pub fn reduce_mut<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut C) -> R,
+    {
+        let mut f = Some(f);
+        let mut result = None;
+
+        self.target.reduce_mut(&mut |child| {
+            if let Some(f) = f.take() {
+                result = Some(f(child));
+            }
+        });
+
+        result.expect("result not initialized")
+    }
+
+    /// Like [Self::reduce_mut] but from a callback.
+    pub fn reduce_mut_callback<F, R, E>(&self, f: F) -> Callback<E>
+    where
+        F: Fn(&mut C) -> R + 'static,
+        E: 'static,
+    {
+        let dispatch = self.clone();
+        Callback::from(move |_| {
+            dispatch.reduce_mut(&f);
+        })
+    }
+
+    /// Similar to [Self::reduce_mut_callback] but also provides the fired event.
+    pub fn reduce_mut_callback_with<F, R, E>(&self, f: F) -> Callback<E>
+    where
+        F: Fn(&mut C, E) -> R + 'static,
+        E: 'static,
+    {
+        let dispatch = self.clone();
+        Callback::from(move |e: E| {
+            dispatch.reduce_mut(|child| f(child, e));
+        })
+    }
+}
+
+impl<C> Clone for LensDispatch<C> {
+    fn clone(&self) -> Self {
+        Self {
+            target: self.target.clone(),
+            _subscription: self._subscription.clone(),
+        }
+    }
+}
+
+impl<C> PartialEq for LensDispatch<C> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.target, &other.target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::Dispatch;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Profile {
+        name: String,
+        city: String,
+    }
+    impl Store for Profile {
+        fn new(_cx: &Context) -> Self {
+            Self {
+                name: "Alice".to_string(),
+                city: "Paris".to_string(),
+            }
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    fn city(cx: &Context) -> LensDispatch<String> {
+        Dispatch::<Profile>::new(cx).lens((
+            |profile: &Profile| profile.city.clone(),
+            |profile: &mut Profile, city| profile.city = city,
+        ))
+    }
+
+    #[test]
+    fn writes_go_to_parent() {
+        let cx = Context::new();
+        let city = city(&cx);
+
+        city.set("Lisbon".to_string());
+        assert_eq!(cx.get::<Profile>().city, "Lisbon");
+
+        city.reduce_mut(|city| city.push('!'));
+        assert_eq!(*city.get(), "Lisbon!");
+        assert_eq!(cx.get::<Profile>().name, "Alice");
+    }
+
+    #[test]
+    fn reads_project_from_parent() {
+        let cx = Context::new();
+        let city = city(&cx);
+
+        Dispatch::<Profile>::new(&cx).reduce_mut(|profile| profile.city = "Rome".to_string());
+
+        assert_eq!(*city.get(), "Rome");
+    }
+
+    #[test]
+    fn notified_only_when_child_changes() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _city = {
+            let calls = calls.clone();
+            city(&cx).subscribe(move |_: Rc<String>| calls.set(calls.get() + 1))
+        };
+        assert_eq!(calls.get(), 1);
+
+        Dispatch::<Profile>::new(&cx).reduce_mut(|profile| profile.name = "Bob".to_string());
+        assert_eq!(calls.get(), 1);
+
+        Dispatch::<Profile>::new(&cx).reduce_mut(|profile| profile.city = "Rome".to_string());
+        assert_eq!(calls.get(), 2);
+    }
+}
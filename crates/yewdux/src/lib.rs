@@ -30,18 +30,36 @@
 //! ```
 #![allow(clippy::needless_doctest_main)]
 
+pub mod action_log;
 mod anymap;
+pub mod computed;
 pub mod context;
 pub mod context_provider;
 pub mod derived_from;
+#[cfg(feature = "devtools")]
+pub mod devtools;
 pub mod dispatch;
+pub mod effect;
+pub mod family;
 pub mod functional;
+#[cfg(feature = "future")]
+pub mod future;
+mod graph;
+pub mod hydrate;
+pub mod introspection;
+pub mod lens;
 pub mod listener;
+pub mod middleware;
 pub mod mrc;
+pub mod scheduler;
+pub mod selector;
 #[cfg(any(feature = "doctests", target_arch = "wasm32"))]
 pub mod storage;
 pub mod store;
 mod subscriber;
+#[cfg(feature = "sync")]
+pub mod sync;
+pub mod transaction;
 
 // Used by macro.
 #[doc(hidden)]
@@ -55,14 +73,18 @@ pub mod prelude {
     //! Default exports
 
     pub use crate::{
-        context_provider::YewduxRoot,
-        derived_from::{DerivedFrom, DerivedFromMut},
+        context_provider::{YewduxRoot, YewduxScope},
+        derived_from::{DerivedFrom, DerivedFromMany, DerivedFromMut},
         dispatch::Dispatch,
+        family::KeyedStore,
         functional::{
-            use_dispatch, use_selector, use_selector_eq, use_selector_eq_with_deps,
-            use_selector_with_deps, use_store, use_store_value,
+            use_dispatch, use_lens_value, use_selector, use_selector_eq,
+            use_selector_eq_with_deps, use_selector_with_deps, use_store, use_store_keyed,
+            use_store_value,
         },
+        lens::{Lens, LensDispatch},
         listener::{init_listener, Listener},
-        store::{Reducer, Store},
+        middleware::Middleware,
+        store::{Reducer, Store, TryReducer},
     };
 }
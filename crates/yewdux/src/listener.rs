@@ -1,4 +1,8 @@
-use std::rc::Rc;
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+    rc::Rc,
+};
 
 use crate::{context::Context, dispatch::Dispatch, store::Store};
 
@@ -25,17 +29,67 @@ impl<L: Listener> Store for ListenerStore<L> {
 
 /// Initiate a [Listener]. Does nothing if listener is already initiated.
 pub fn init_listener<L: Listener, F: FnOnce() -> L>(new_listener: F, cx: &Context) {
-    cx.get_or_init(|cx| {
+    // Listeners live with their store, so they see the same state as its subscribers.
+    cx.owner::<L::Store>().get_or_init_hidden(|cx| {
         let dispatch = {
             let listener = new_listener();
-            let cx = cx.clone();
-            Dispatch::new(&cx).subscribe_silent(move |state| listener.on_change(&cx, state))
+            let id = {
+                let cx = cx.clone();
+                cx.clone()
+                    .subscribe_passive(move |state| listener.on_change(&cx, state))
+            };
+
+            Dispatch {
+                _subscriber_id: Some(Rc::new(id)),
+                cx: cx.clone(),
+            }
         };
 
+        cx.internal::<Listeners>().with_mut(|listeners| {
+            listeners
+                .0
+                .entry(TypeId::of::<L::Store>())
+                .or_default()
+                .push((type_name::<L>(), remove_listener::<L>))
+        });
+
         ListenerStore::<L>(dispatch)
     });
 }
 
+/// Removes a listener from context.
+type Remove = fn(&Context);
+
+/// Type names of listeners, and functions that remove them, by type id of their store.
+#[derive(Default)]
+struct Listeners(HashMap<TypeId, Vec<(&'static str, Remove)>>);
+
+fn remove_listener<L: Listener>(cx: &Context) {
+    cx.remove::<ListenerStore<L>>();
+}
+
+/// Remove all listeners of store `S`.
+pub(crate) fn remove_listeners<S: Store>(cx: &Context) {
+    let listeners = cx
+        .internal::<Listeners>()
+        .with_mut(|listeners| listeners.0.remove(&TypeId::of::<S>()))
+        .unwrap_or_default();
+
+    for (_, remove) in listeners {
+        remove(cx);
+    }
+}
+
+/// Type names of all listeners of the store with given type id.
+pub(crate) fn listener_names(cx: &Context, type_id: TypeId) -> Vec<&'static str> {
+    cx.internal::<Listeners>()
+        .borrow()
+        .0
+        .get(&type_id)
+        .map(|listeners| listeners.iter().map(|(name, _)| *name).collect())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
 
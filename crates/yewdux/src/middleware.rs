@@ -0,0 +1,351 @@
+//! Hooks that run around every state change in a [Context].
+//!
+//! Useful for cross-cutting concerns like logging, validation, or analytics, which would otherwise
+//! need a separate [Listener](crate::Listener) for every store.
+//!
+//! ```
+//! use std::rc::Rc;
+//!
+//! use yewdux::{middleware::Change, prelude::*};
+//!
+//! struct Logger;
+//! impl Middleware for Logger {
+//!     fn on_reduce(&self, _cx: &yewdux::Context, change: &mut Change) {
+//!         if change.should_notify() {
+//!             println!("{} changed", change.store_name());
+//!         }
+//!     }
+//! }
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Counter(u32);
+//!
+//! /// Counter may never exceed 10.
+//! struct Limit;
+//! impl Middleware for Limit {
+//!     fn on_reduce(&self, _cx: &yewdux::Context, change: &mut Change) {
+//!         if let Some(counter) = change.new_state::<Counter>() {
+//!             if counter.0 > 10 {
+//!                 change.replace(Rc::new(Counter(10)));
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! cx.add_middleware(Logger);
+//! cx.add_middleware(Limit);
+//!
+//! cx.set(Counter(42));
+//! assert_eq!(cx.get::<Counter>().0, 10);
+//! ```
+use std::{
+    any::{type_name, Any, TypeId},
+    rc::Rc,
+};
+
+use crate::{store::Store, Context};
+
+/// Runs around every state change in a [Context]. See the [module docs](self) for an example.
+pub trait Middleware: 'static {
+    /// Called after a reducer produces new state, but before that state is saved and subscribers
+    /// are notified.
+    fn on_reduce(&self, cx: &Context, change: &mut Change);
+}
+
+/// A pending state change, as seen by [Middleware].
+pub struct Change {
+    store_name: &'static str,
+    type_id: TypeId,
+    old: Rc<dyn Any>,
+    new: Rc<dyn Any>,
+    should_notify: bool,
+    vetoed: bool,
+}
+
+impl std::fmt::Debug for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Change")
+            .field("store_name", &self.store_name)
+            .field("should_notify", &self.should_notify)
+            .field("vetoed", &self.vetoed)
+            .finish()
+    }
+}
+
+impl Change {
+    /// Type name of the store being changed.
+    pub fn store_name(&self) -> &'static str {
+        self.store_name
+    }
+
+    /// Whether this is a change to store `S`.
+    pub fn is<S: Store>(&self) -> bool {
+        self.type_id == TypeId::of::<S>()
+    }
+
+    /// State before the change, if this is a change to store `S`.
+    pub fn old_state<S: Store>(&self) -> Option<Rc<S>> {
+        Rc::clone(&self.old).downcast().ok()
+    }
+
+    /// State after the change, if this is a change to store `S`.
+    pub fn new_state<S: Store>(&self) -> Option<Rc<S>> {
+        Rc::clone(&self.new).downcast().ok()
+    }
+
+    /// Whether subscribers will be notified about this change.
+    pub fn should_notify(&self) -> bool {
+        self.should_notify
+    }
+
+    /// Override whether subscribers will be notified about this change.
+    pub fn set_should_notify(&mut self, should_notify: bool) {
+        self.should_notify = should_notify;
+    }
+
+    /// Replace the new state. Whether subscribers are notified is decided again with
+    /// [Store::should_notify].
+    ///
+    /// # Panics
+    ///
+    /// Panics if this isn't a change to store `S`.
+    pub fn replace<S: Store>(&mut self, state: Rc<S>) {
+        let old = self.old_state::<S>().unwrap_or_else(|| {
+            panic!(
+                "Cannot replace {} with {}",
+                self.store_name,
+                type_name::<S>()
+            )
+        });
+
+        self.should_notify = state.should_notify(&old);
+        self.new = state;
+    }
+
+    /// Discard this change. State is left untouched, and subscribers are not notified. Remaining
+    /// middleware is skipped.
+    pub fn veto(&mut self) {
+        self.vetoed = true;
+    }
+
+    /// Whether this change has been discarded.
+    pub fn is_vetoed(&self) -> bool {
+        self.vetoed
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Middlewares(Vec<Rc<dyn Middleware>>);
+
+impl Middlewares {
+    pub(crate) fn push<M: Middleware>(&mut self, middleware: M) {
+        self.0.push(Rc::new(middleware));
+    }
+}
+
+/// Run all middleware for a change to store `S`. Returns the state to save, and whether
+/// subscribers should be notified, or `None` if the change was vetoed.
+pub(crate) fn run<S: Store>(
+    cx: &Context,
+    old: &Rc<S>,
+    new: Rc<S>,
+    should_notify: bool,
+) -> Option<(Rc<S>, bool)> {
+    // Cloned so middleware may be added from within middleware.
+    let middlewares = cx.internal::<Middlewares>().borrow().0.clone();
+    if middlewares.is_empty() {
+        return Some((new, should_notify));
+    }
+
+    let mut change = Change {
+        store_name: type_name::<S>(),
+        type_id: TypeId::of::<S>(),
+        old: Rc::clone(old) as Rc<dyn Any>,
+        new,
+        should_notify,
+        vetoed: false,
+    };
+
+    for middleware in middlewares {
+        middleware.on_reduce(cx, &mut change);
+        if change.vetoed {
+            return None;
+        }
+    }
+
+    let new = change
+        .new
+        .downcast()
+        .expect("Middleware changed store type");
+    Some((new, change.should_notify))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    /// Store name, old value, new value, and whether subscribers should be notified.
+    type Entry = (&'static str, u32, u32, bool);
+
+    struct Log(Rc<RefCell<Vec<Entry>>>);
+    impl Middleware for Log {
+        fn on_reduce(&self, _cx: &Context, change: &mut Change) {
+            let old = change.old_state::<TestState>().unwrap();
+            let new = change.new_state::<TestState>().unwrap();
+            self.0
+                .borrow_mut()
+                .push((change.store_name(), old.0, new.0, change.should_notify()));
+        }
+    }
+
+    struct Veto;
+    impl Middleware for Veto {
+        fn on_reduce(&self, _cx: &Context, change: &mut Change) {
+            change.veto();
+        }
+    }
+
+    struct Double;
+    impl Middleware for Double {
+        fn on_reduce(&self, _cx: &Context, change: &mut Change) {
+            if let Some(state) = change.new_state::<TestState>() {
+                change.replace(Rc::new(TestState(state.0 * 2)));
+            }
+        }
+    }
+
+    struct Silence;
+    impl Middleware for Silence {
+        fn on_reduce(&self, _cx: &Context, change: &mut Change) {
+            change.set_should_notify(false);
+        }
+    }
+
+    fn count_notifications(
+        cx: &Context,
+    ) -> (Rc<RefCell<u32>>, crate::subscriber::SubscriberId<TestState>) {
+        let calls = Rc::new(RefCell::new(0));
+        let id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| *calls.borrow_mut() += 1)
+        };
+
+        (calls, id)
+    }
+
+    #[test]
+    fn middleware_sees_changes() {
+        let cx = Context::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        cx.add_middleware(Log(log.clone()));
+
+        cx.set(TestState(1));
+        cx.set(TestState(1));
+
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                (type_name::<TestState>(), 0, 1, true),
+                (type_name::<TestState>(), 1, 1, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn middleware_runs_for_try_reduce() {
+        let cx = Context::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        cx.add_middleware(Log(log.clone()));
+
+        cx.try_reduce_mut(|state: &mut TestState| {
+            state.0 = 1;
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+
+        assert_eq!(log.borrow().len(), 1);
+    }
+
+    #[test]
+    fn veto_leaves_state_untouched() {
+        let cx = Context::new();
+        let (calls, _id) = count_notifications(&cx);
+        cx.add_middleware(Veto);
+
+        cx.set(TestState(1));
+
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(*calls.borrow(), 0);
+    }
+
+    #[test]
+    fn veto_skips_remaining_middleware() {
+        let cx = Context::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        cx.add_middleware(Veto);
+        cx.add_middleware(Log(log.clone()));
+
+        cx.set(TestState(1));
+
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn replace_changes_new_state() {
+        let cx = Context::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        cx.add_middleware(Double);
+        cx.add_middleware(Log(log.clone()));
+
+        cx.set(TestState(2));
+
+        assert_eq!(cx.get::<TestState>().0, 4);
+        assert_eq!(log.borrow()[0].2, 4);
+    }
+
+    #[test]
+    fn replace_decides_notify_again() {
+        struct Reset;
+        impl Middleware for Reset {
+            fn on_reduce(&self, _cx: &Context, change: &mut Change) {
+                change.replace(change.old_state::<TestState>().unwrap());
+            }
+        }
+
+        let cx = Context::new();
+        let (calls, _id) = count_notifications(&cx);
+        cx.add_middleware(Reset);
+
+        cx.set(TestState(1));
+
+        assert_eq!(cx.get::<TestState>().0, 0);
+        assert_eq!(*calls.borrow(), 0);
+    }
+
+    #[test]
+    fn can_override_notify() {
+        let cx = Context::new();
+        let (calls, _id) = count_notifications(&cx);
+        cx.add_middleware(Silence);
+
+        cx.set(TestState(1));
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+        assert_eq!(*calls.borrow(), 0);
+    }
+}
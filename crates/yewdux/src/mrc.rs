@@ -25,7 +25,7 @@
 use std::{
     cell::{Cell, RefCell},
     ops::{Deref, DerefMut},
-    rc::Rc,
+    rc::{Rc, Weak},
 };
 
 use serde::{Deserialize, Serialize};
@@ -80,6 +80,33 @@ impl<T> Mrc<T> {
         self.nonce.set(nonce());
         self.inner.borrow_mut()
     }
+
+    /// Whether both point to the same value, whether or not either was changed since.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakMrc<T> {
+        WeakMrc {
+            inner: Rc::downgrade(&self.inner),
+            nonce: self.nonce.get(),
+        }
+    }
+}
+
+/// Reference to an [Mrc] that doesn't keep its value alive.
+pub(crate) struct WeakMrc<T> {
+    inner: Weak<RefCell<T>>,
+    nonce: u32,
+}
+
+impl<T> WeakMrc<T> {
+    pub(crate) fn upgrade(&self) -> Option<Mrc<T>> {
+        Some(Mrc {
+            inner: self.inner.upgrade()?,
+            nonce: Cell::new(self.nonce),
+        })
+    }
 }
 
 impl<T: Store> Store for Mrc<T> {
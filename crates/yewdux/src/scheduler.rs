@@ -0,0 +1,490 @@
+//! Scheduling when subscribers are notified of changes.
+//!
+//! By default subscribers are notified immediately after every change. A [NotifyScheduler] may
+//! instead delay notifications, so changes made in quick succession, like one per keystroke, are
+//! collapsed into a single notification with the latest state.
+//!
+//! Delays are run by a [Timer]. The default one uses the browser, or the Yew runtime on native
+//! targets. A [ManualTimer] is advanced by hand, which is useful for tests.
+//!
+//! ```
+//! use std::{cell::Cell, rc::Rc, time::Duration};
+//!
+//! use yewdux::{
+//!     prelude::*,
+//!     scheduler::{ManualTimer, NotifyScheduler},
+//! };
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Search(String);
+//!
+//! let cx = yewdux::Context::new();
+//! let timer = ManualTimer::new();
+//! cx.set_timer(timer.clone());
+//! cx.set_store_scheduler::<Search>(NotifyScheduler::Debounce(Duration::from_millis(300)));
+//!
+//! let calls = Rc::new(Cell::new(0));
+//! let dispatch = {
+//!     let calls = calls.clone();
+//!     Dispatch::<Search>::new(&cx).subscribe_silent(move |_| calls.set(calls.get() + 1))
+//! };
+//!
+//! for query in ["y", "ye", "yew"] {
+//!     dispatch.set(Search(query.to_string()));
+//! }
+//! assert_eq!(calls.get(), 0);
+//!
+//! timer.advance(Duration::from_millis(300));
+//! assert_eq!(calls.get(), 1);
+//! ```
+use std::{any::TypeId, collections::HashMap, rc::Rc, time::Duration};
+
+use crate::{effect::Effect, mrc::Mrc, store::Store, Context};
+
+/// When subscribers are notified of a change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotifyScheduler {
+    /// As soon as state changes.
+    #[default]
+    Immediate,
+    /// Once the current task completes.
+    Microtask,
+    /// Before the next frame is rendered.
+    AnimationFrame,
+    /// Once state hasn't changed for the given duration.
+    Debounce(Duration),
+}
+
+pub type Task = Box<dyn FnOnce()>;
+
+/// Runs tasks later, for a [NotifyScheduler].
+pub trait Timer: 'static {
+    /// Run `task` once the current task completes.
+    fn microtask(&self, task: Task);
+    /// Run `task` before the next frame is rendered.
+    fn animation_frame(&self, task: Task);
+    /// Run `task` after `delay`.
+    fn timeout(&self, delay: Duration, task: Task);
+}
+
+/// Default [Timer]. Uses the Yew runtime, and `requestAnimationFrame` in the browser.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PlatformTimer;
+
+impl Timer for PlatformTimer {
+    fn microtask(&self, task: Task) {
+        yew::platform::spawn_local(async move { task() });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn animation_frame(&self, task: Task) {
+        use wasm_bindgen::{prelude::Closure, JsCast};
+
+        let callback = Closure::once_into_js(task);
+        web_sys::window()
+            .expect("window not found")
+            .request_animation_frame(callback.unchecked_ref())
+            .expect("unable to request animation frame");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn animation_frame(&self, task: Task) {
+        // No frames to wait for, so run at roughly 60 per second.
+        self.timeout(Duration::from_millis(16), task);
+    }
+
+    fn timeout(&self, delay: Duration, task: Task) {
+        yew::platform::spawn_local(async move {
+            yew::platform::time::sleep(delay).await;
+            task();
+        });
+    }
+}
+
+#[derive(Default)]
+struct Scheduled {
+    now: Duration,
+    microtasks: Vec<Task>,
+    frames: Vec<Task>,
+    /// Due time, and order of scheduling.
+    timeouts: Vec<(Duration, u64, Task)>,
+    scheduled: u64,
+}
+
+/// A [Timer] that only runs tasks when told to. Clones share the same tasks.
+#[derive(Clone, Default)]
+pub struct ManualTimer(Mrc<Scheduled>);
+
+impl std::fmt::Debug for ManualTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManualTimer")
+            .field("now", &self.0.borrow().now)
+            .finish()
+    }
+}
+
+impl ManualTimer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Run all microtasks, including those scheduled while running.
+    pub fn run_microtasks(&self) {
+        loop {
+            let tasks = self.0.with_mut(|s| std::mem::take(&mut s.microtasks));
+            if tasks.is_empty() {
+                break;
+            }
+
+            for task in tasks {
+                task();
+            }
+        }
+    }
+
+    /// Run microtasks, then the tasks waiting for the next frame.
+    pub fn next_frame(&self) {
+        self.run_microtasks();
+        let tasks = self.0.with_mut(|s| std::mem::take(&mut s.frames));
+        for task in tasks {
+            task();
+            self.run_microtasks();
+        }
+    }
+
+    /// Move time forward by `duration`, running microtasks and timeouts that become due, in order.
+    pub fn advance(&self, duration: Duration) {
+        self.run_microtasks();
+        let until = self.0.borrow().now + duration;
+        loop {
+            let task = self.0.with_mut(|s| {
+                let (index, _) = s
+                    .timeouts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (due, _, _))| *due <= until)
+                    .min_by_key(|(_, (due, order, _))| (*due, *order))?;
+                let (due, _, task) = s.timeouts.remove(index);
+                s.now = due;
+                Some(task)
+            });
+            let Some(task) = task else {
+                break;
+            };
+
+            task();
+            self.run_microtasks();
+        }
+        self.0.with_mut(|s| s.now = until);
+    }
+}
+
+impl Timer for ManualTimer {
+    fn microtask(&self, task: Task) {
+        self.0.with_mut(|s| s.microtasks.push(task));
+    }
+
+    fn animation_frame(&self, task: Task) {
+        self.0.with_mut(|s| s.frames.push(task));
+    }
+
+    fn timeout(&self, delay: Duration, task: Task) {
+        self.0.with_mut(|s| {
+            s.scheduled += 1;
+            let due = s.now + delay;
+            let order = s.scheduled;
+            s.timeouts.push((due, order, task));
+        });
+    }
+}
+
+/// Notification settings, shared by every context with the same root.
+#[derive(Default)]
+pub(crate) struct Scheduling {
+    pub(crate) default: NotifyScheduler,
+    pub(crate) stores: HashMap<TypeId, NotifyScheduler>,
+    pub(crate) timer: Option<Rc<dyn Timer>>,
+}
+
+impl Scheduling {
+    pub(crate) fn scheduler<S: Store>(&self) -> NotifyScheduler {
+        self.stores
+            .get(&TypeId::of::<S>())
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    pub(crate) fn timer(&self) -> Rc<dyn Timer> {
+        self.timer.clone().unwrap_or_else(|| Rc::new(PlatformTimer))
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    scheduled: bool,
+    generation: u64,
+    /// Effects to run once the scheduled notification is sent.
+    effects: Vec<Effect>,
+}
+
+/// Notifications scheduled for stores owned by a context.
+#[derive(Default)]
+struct Notifications(HashMap<TypeId, Pending>);
+
+/// Notify subscribers of `S` in `cx`, which owns it, according to `scheduler`.
+pub(crate) fn schedule<S: Store>(cx: &Context, scheduler: NotifyScheduler, timer: &dyn Timer) {
+    let (generation, coalesced) = cx.internal::<Notifications>().with_mut(|notifications| {
+        let pending = notifications.0.entry(TypeId::of::<S>()).or_default();
+        // A scheduled microtask or frame sends the latest state anyway. Debounced notifications
+        // are replaced instead, to restart the delay.
+        let coalesced = pending.scheduled && !matches!(scheduler, NotifyScheduler::Debounce(_));
+        if !coalesced {
+            pending.scheduled = true;
+            pending.generation += 1;
+        }
+
+        (pending.generation, coalesced)
+    });
+    if coalesced {
+        return;
+    }
+
+    let notify = {
+        let cx = cx.downgrade();
+        Box::new(move || {
+            let Some(cx) = cx.upgrade() else {
+                return;
+            };
+
+            let (current, effects) = cx.internal::<Notifications>().with_mut(|notifications| {
+                let pending = notifications.0.entry(TypeId::of::<S>()).or_default();
+                let current = pending.generation == generation;
+                if !current {
+                    return (false, Vec::new());
+                }
+
+                pending.scheduled = false;
+                (true, std::mem::take(&mut pending.effects))
+            });
+            // Skipped if replaced by a later notification. The store may also have been removed.
+            if let Some(state) = cx.peek::<S>().filter(|_| current) {
+                cx.deliver_now(state);
+            }
+            cx.run_effects(effects);
+        })
+    };
+
+    match scheduler {
+        NotifyScheduler::Immediate => notify(),
+        NotifyScheduler::Microtask => timer.microtask(notify),
+        NotifyScheduler::AnimationFrame => timer.animation_frame(notify),
+        NotifyScheduler::Debounce(delay) => timer.timeout(delay, notify),
+    }
+}
+
+/// Hold `effects` until the scheduled notification for `S` in `cx`, which owns it, is sent.
+/// Returns them instead if no notification is scheduled.
+pub(crate) fn defer_effects<S: Store>(cx: &Context, effects: Vec<Effect>) -> Vec<Effect> {
+    cx.internal::<Notifications>().with_mut(|notifications| {
+        match notifications.0.get_mut(&TypeId::of::<S>()) {
+            Some(pending) if pending.scheduled => {
+                pending.effects.extend(effects);
+                Vec::new()
+            }
+            _ => effects,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{context::DispatchMode, Dispatch};
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState2(u32);
+    impl Store for TestState2 {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    fn setup() -> (Context, ManualTimer) {
+        let cx = Context::new();
+        let timer = ManualTimer::new();
+        cx.set_timer(timer.clone());
+
+        (cx, timer)
+    }
+
+    /// Subscribe to `TestState`, recording the states received.
+    fn record(cx: &Context) -> (Mrc<Vec<u32>>, Dispatch<TestState>) {
+        let seen = Mrc::new(Vec::new());
+        let dispatch = {
+            let seen = seen.clone();
+            Dispatch::new(cx)
+                .subscribe_silent(move |state: Rc<TestState>| seen.borrow_mut().push(state.0))
+        };
+
+        (seen, dispatch)
+    }
+
+    fn take(seen: &Mrc<Vec<u32>>) -> Vec<u32> {
+        std::mem::take(&mut seen.borrow_mut())
+    }
+
+    #[test]
+    fn immediate_by_default() {
+        let (cx, _timer) = setup();
+        let (seen, dispatch) = record(&cx);
+
+        dispatch.set(TestState(1));
+
+        assert_eq!(take(&seen), vec![1]);
+    }
+
+    #[test]
+    fn microtask_collapses_changes() {
+        let (cx, timer) = setup();
+        let (seen, dispatch) = record(&cx);
+        cx.set_notify_scheduler(NotifyScheduler::Microtask);
+
+        dispatch.set(TestState(1));
+        dispatch.set(TestState(2));
+        dispatch.set(TestState(3));
+        assert!(take(&seen).is_empty());
+
+        timer.run_microtasks();
+        assert_eq!(take(&seen), vec![3]);
+
+        dispatch.set(TestState(4));
+        timer.run_microtasks();
+        assert_eq!(take(&seen), vec![4]);
+    }
+
+    #[test]
+    fn animation_frame_waits_for_frame() {
+        let (cx, timer) = setup();
+        let (seen, dispatch) = record(&cx);
+        cx.set_notify_scheduler(NotifyScheduler::AnimationFrame);
+
+        dispatch.set(TestState(1));
+        dispatch.set(TestState(2));
+        timer.run_microtasks();
+        assert!(take(&seen).is_empty());
+
+        timer.next_frame();
+        assert_eq!(take(&seen), vec![2]);
+    }
+
+    #[test]
+    fn debounce_waits_for_changes_to_stop() {
+        let (cx, timer) = setup();
+        let (seen, dispatch) = record(&cx);
+        cx.set_notify_scheduler(NotifyScheduler::Debounce(Duration::from_millis(100)));
+
+        dispatch.set(TestState(1));
+        timer.advance(Duration::from_millis(60));
+        dispatch.set(TestState(2));
+        timer.advance(Duration::from_millis(60));
+        assert!(take(&seen).is_empty());
+
+        timer.advance(Duration::from_millis(40));
+        assert_eq!(take(&seen), vec![2]);
+    }
+
+    #[test]
+    fn batched_changes_are_scheduled() {
+        let (cx, timer) = setup();
+        let (seen, _dispatch) = record(&cx);
+        cx.set_store_scheduler::<TestState>(NotifyScheduler::Debounce(Duration::from_millis(100)));
+
+        cx.batch(|cx| cx.set(TestState(1)));
+        assert!(take(&seen).is_empty());
+
+        timer.advance(Duration::from_millis(100));
+        assert_eq!(take(&seen), vec![1]);
+    }
+
+    #[test]
+    fn store_scheduler_overrides_default() {
+        let (cx, timer) = setup();
+        let (seen, dispatch) = record(&cx);
+        cx.set_notify_scheduler(NotifyScheduler::Microtask);
+        cx.set_store_scheduler::<TestState>(NotifyScheduler::Immediate);
+        let notified = Mrc::new(false);
+        let _id = {
+            let notified = notified.clone();
+            cx.subscribe_silent(move |_: Rc<TestState2>| *notified.borrow_mut() = true)
+        };
+
+        dispatch.set(TestState(1));
+        cx.set(TestState2(1));
+
+        assert_eq!(take(&seen), vec![1]);
+        assert!(!*notified.borrow());
+        timer.run_microtasks();
+        assert!(*notified.borrow());
+    }
+
+    #[test]
+    fn scheduled_notification_is_queued() {
+        let (cx, timer) = setup();
+        cx.set_dispatch_mode(DispatchMode::Queued);
+        cx.set_store_scheduler::<TestState>(NotifyScheduler::Microtask);
+        let seen = Mrc::new(Vec::new());
+        let _first = {
+            let cx = cx.clone();
+            cx.clone()
+                .subscribe_silent(move |state: Rc<TestState>| cx.set(TestState2(state.0)))
+        };
+        let _second = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState>| {
+                seen.borrow_mut().push(("state", state.0))
+            })
+        };
+        let _other = {
+            let seen = seen.clone();
+            cx.subscribe_silent(move |state: Rc<TestState2>| {
+                seen.borrow_mut().push(("other", state.0))
+            })
+        };
+
+        cx.set(TestState(1));
+        timer.run_microtasks();
+
+        // Everyone was notified of the first change before the one made by a subscriber.
+        assert_eq!(*seen.borrow(), vec![("state", 1), ("other", 1)]);
+    }
+
+    #[test]
+    fn removed_store_is_not_notified() {
+        let (cx, timer) = setup();
+        let (seen, dispatch) = record(&cx);
+        cx.set_notify_scheduler(NotifyScheduler::Microtask);
+
+        dispatch.set(TestState(1));
+        cx.remove::<TestState>();
+        timer.run_microtasks();
+
+        assert!(take(&seen).is_empty());
+        assert!(cx.store_info::<TestState>().is_none());
+    }
+}
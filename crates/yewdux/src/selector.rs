@@ -0,0 +1,171 @@
+//! Subscribing to part of a store, outside of components.
+//!
+//! Like [use_selector](crate::functional::use_selector), a [Selection] calls back only when the
+//! selected value changes, but it works anywhere: struct components, listeners, or plain Rust.
+//!
+//! ```
+//! use std::{cell::Cell, rc::Rc};
+//!
+//! use yewdux::prelude::*;
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct State {
+//!     count: u32,
+//!     name: String,
+//! }
+//!
+//! let cx = yewdux::Context::new();
+//! let dispatch = Dispatch::<State>::new(&cx);
+//! let calls = Rc::new(Cell::new(0));
+//!
+//! let count = {
+//!     let calls = calls.clone();
+//!     dispatch.subscribe_selector(
+//!         |state: &State| state.count,
+//!         move |_count: Rc<u32>| calls.set(calls.get() + 1),
+//!     )
+//! };
+//!
+//! dispatch.reduce_mut(|state| state.name = "Yew".to_string());
+//! assert_eq!(calls.get(), 0);
+//!
+//! dispatch.reduce_mut(|state| state.count += 1);
+//! assert_eq!(calls.get(), 1);
+//! assert_eq!(*count.get(), 1);
+//! ```
+use std::rc::Rc;
+
+use crate::{
+    mrc::Mrc,
+    store::Store,
+    subscriber::{Callable, SubscriberId},
+    Context,
+};
+
+/// A subscription to a value selected from store `S`. Unsubscribes when dropped.
+pub struct Selection<S: Store, R> {
+    value: Mrc<Rc<R>>,
+    _subscriber_id: SubscriberId<S>,
+}
+
+impl<S: Store, R> std::fmt::Debug for Selection<S, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Selection").finish()
+    }
+}
+
+impl<S: Store, R> Selection<S, R> {
+    /// Subscribe to the value `selector` returns, calling `on_change` whenever it changes
+    /// according to `eq`. `on_change` isn't called for the initial value.
+    pub(crate) fn new<F, E, C>(cx: &Context, selector: F, eq: E, on_change: C) -> Self
+    where
+        R: 'static,
+        F: Fn(&S) -> R + 'static,
+        E: Fn(&R, &R) -> bool + 'static,
+        C: Callable<R>,
+    {
+        let value = Mrc::new(Rc::new(selector(&cx.get::<S>())));
+        let subscriber_id = {
+            let value = value.clone();
+            cx.subscribe_silent(move |state: Rc<S>| {
+                let selected = selector(&state);
+                if eq(&value.borrow(), &selected) {
+                    return;
+                }
+
+                let selected = Rc::new(selected);
+                *value.borrow_mut() = Rc::clone(&selected);
+                on_change.call(selected);
+            })
+        };
+
+        Self {
+            value,
+            _subscriber_id: subscriber_id,
+        }
+    }
+
+    /// The currently selected value.
+    pub fn get(&self) -> Rc<R> {
+        Rc::clone(&self.value.borrow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::Dispatch;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState {
+        a: u32,
+        b: u32,
+    }
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self { a: 0, b: 0 }
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    fn counter() -> (Rc<Cell<u32>>, impl Fn(Rc<u32>)) {
+        let calls = Rc::new(Cell::new(0));
+        let on_change = {
+            let calls = calls.clone();
+            move |_| calls.set(calls.get() + 1)
+        };
+
+        (calls, on_change)
+    }
+
+    #[test]
+    fn calls_back_only_when_selected_value_changes() {
+        let cx = Context::new();
+        let (calls, on_change) = counter();
+        let selection = cx.select(|state: &TestState| state.a, on_change);
+        assert_eq!(calls.get(), 0);
+
+        cx.set(TestState { a: 0, b: 1 });
+        assert_eq!(calls.get(), 0);
+
+        cx.set(TestState { a: 1, b: 1 });
+        assert_eq!(calls.get(), 1);
+        assert_eq!(*selection.get(), 1);
+    }
+
+    #[test]
+    fn eq_decides_what_is_a_change() {
+        let cx = Context::new();
+        let dispatch = Dispatch::<TestState>::new(&cx);
+        let (calls, on_change) = counter();
+        let _selection = dispatch.subscribe_selector_eq(
+            |state: &TestState| state.a,
+            |old, new| old / 10 == new / 10,
+            on_change,
+        );
+
+        dispatch.reduce_mut(|state| state.a = 5);
+        assert_eq!(calls.get(), 0);
+
+        dispatch.reduce_mut(|state| state.a = 10);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn unsubscribes_when_dropped() {
+        let cx = Context::new();
+        let (calls, on_change) = counter();
+        let selection = cx.select(|state: &TestState| state.a, on_change);
+
+        drop(selection);
+        cx.set(TestState { a: 1, b: 0 });
+
+        assert_eq!(calls.get(), 0);
+        assert_eq!(cx.subscriber_count::<TestState>(), 0);
+    }
+}
@@ -13,6 +13,44 @@ pub trait Store: 'static {
     /// Indicate whether or not subscribers should be notified about this change. Usually this
     /// should be set to `self != old`.
     fn should_notify(&self, old: &Self) -> bool;
+
+    /// Called when the store gets its first subscriber. Useful for starting work that is only
+    /// needed while the store is in use, like polling or opening a websocket. Listeners don't
+    /// count as subscribers.
+    ///
+    /// With the derive macro, hooks are set with attributes:
+    ///
+    /// ```
+    /// use yewdux::prelude::*;
+    ///
+    /// #[derive(Default, Clone, PartialEq, Store)]
+    /// #[store(on_active = "start_polling", on_inactive = "stop_polling")]
+    /// struct Prices(Vec<u32>);
+    ///
+    /// #[derive(Default, Clone, PartialEq, Store)]
+    /// struct Polling(bool);
+    ///
+    /// fn start_polling(_prices: &Prices, cx: &yewdux::Context) {
+    ///     cx.set(Polling(true));
+    /// }
+    ///
+    /// fn stop_polling(_prices: &Prices, cx: &yewdux::Context) {
+    ///     cx.set(Polling(false));
+    /// }
+    ///
+    /// let cx = yewdux::Context::new();
+    /// let dispatch = Dispatch::<Prices>::new(&cx).subscribe(|_| {});
+    /// assert!(cx.get::<Polling>().0);
+    ///
+    /// drop(dispatch);
+    /// assert!(!cx.get::<Polling>().0);
+    /// ```
+    #[allow(unused_variables)]
+    fn on_active(&self, cx: &Context) {}
+
+    /// Called when the last subscriber of the store is dropped.
+    #[allow(unused_variables)]
+    fn on_inactive(&self, cx: &Context) {}
 }
 
 /// A type that can change state.
@@ -69,3 +107,53 @@ where
         self(state)
     }
 }
+
+/// A type that can change state, but may fail. When it fails, state is left untouched and
+/// subscribers are not notified.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use yewdux::prelude::*;
+///
+/// #[derive(Default, Clone, PartialEq, Eq, Store)]
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// struct Sub(u32);
+///
+/// impl TryReducer<Counter> for Sub {
+///     type Error = String;
+///
+///     fn try_apply(self, counter: Rc<Counter>) -> Result<Rc<Counter>, Self::Error> {
+///         let count = counter
+///             .count
+///             .checked_sub(self.0)
+///             .ok_or_else(|| format!("cannot subtract {} from {}", self.0, counter.count))?;
+///
+///         Ok(Counter { count }.into())
+///     }
+/// }
+///
+/// let dispatch = Dispatch::<Counter>::new(&yewdux::Context::new());
+/// assert!(dispatch.try_apply(Sub(1)).is_err());
+/// ```
+pub trait TryReducer<S> {
+    /// The error returned when this reducer fails.
+    type Error;
+
+    /// Mutate state, or return an error if the change isn't valid.
+    fn try_apply(self, state: Rc<S>) -> Result<Rc<S>, Self::Error>;
+}
+
+impl<F, S, E> TryReducer<S> for F
+where
+    F: FnOnce(Rc<S>) -> Result<Rc<S>, E>,
+{
+    type Error = E;
+
+    fn try_apply(self, state: Rc<S>) -> Result<Rc<S>, Self::Error> {
+        self(state)
+    }
+}
@@ -4,13 +4,27 @@ use std::{any::Any, marker::PhantomData};
 use slab::Slab;
 use yew::Callback;
 
-use crate::{mrc::Mrc, store::Store, Context};
-
-pub(crate) struct Subscribers<S>(pub(crate) Slab<Box<dyn Callable<S>>>);
+use crate::{context::WeakContext, mrc::Mrc, store::Store, Context};
+
+/// Subscribers of store `S`.
+///
+/// Callbacks may subscribe and unsubscribe while being notified. Each notification goes to the
+/// subscribers present when it started: new subscribers don't receive it, and subscribers removed
+/// before their turn are skipped.
+pub(crate) struct Subscribers<S> {
+    pub(crate) slab: Slab<Rc<dyn Callable<S>>>,
+    /// Number of subscribers that keep the store active. Listeners don't count.
+    active: usize,
+    /// Context that owns the store, for lifecycle hooks.
+    cx: Option<WeakContext>,
+}
 
 impl<S: 'static> Store for Subscribers<S> {
-    fn new(_cx: &Context) -> Self {
-        Self(Default::default())
+    fn new(cx: &Context) -> Self {
+        Self {
+            cx: Some(cx.downgrade()),
+            ..Default::default()
+        }
     }
 
     fn should_notify(&self, other: &Self) -> bool {
@@ -19,22 +33,86 @@ impl<S: 'static> Store for Subscribers<S> {
 }
 
 impl<S: Store> Mrc<Subscribers<S>> {
-    pub(crate) fn subscribe<C: Callable<S>>(&self, on_change: C) -> SubscriberId<S> {
-        let key = self.borrow_mut().0.insert(Box::new(on_change));
+    /// Add a subscriber. If `active`, it counts towards keeping the store active, and
+    /// [Store::on_active] is called for the first one.
+    pub(crate) fn subscribe<C: Callable<S>>(&self, on_change: C, active: bool) -> SubscriberId<S> {
+        let (key, activated) = self.with_mut(|subscribers| {
+            let key = subscribers.slab.insert(Rc::new(on_change));
+            if active {
+                subscribers.active += 1;
+            }
+
+            (key, active && subscribers.active == 1)
+        });
+
+        if activated {
+            self.with_store(true, |cx, state| state.on_active(cx));
+        }
+
         SubscriberId {
             subscribers_ref: self.clone(),
             key,
+            active,
             _store_type: Default::default(),
         }
     }
 
-    pub(crate) fn unsubscribe(&mut self, key: usize) {
-        self.borrow_mut().0.remove(key);
+    pub(crate) fn unsubscribe(&mut self, key: usize, active: bool) {
+        let (subscriber, deactivated) = self.with_mut(|subscribers| {
+            let subscriber = subscribers.slab.remove(key);
+            if active {
+                subscribers.active -= 1;
+            }
+
+            (subscriber, active && subscribers.active == 0)
+        });
+        // Dropped outside the borrow, in case it owns another subscription to this store.
+        drop(subscriber);
+
+        if deactivated {
+            self.with_store(false, |cx, state| state.on_inactive(cx));
+        }
+    }
+
+    /// Number of subscribers that keep the store active.
+    pub(crate) fn active(&self) -> usize {
+        self.borrow().active
+    }
+
+    /// Run `f` with the owning context and current state, unless the context was dropped. If not
+    /// `init`, `f` is also skipped when the store doesn't exist.
+    fn with_store(&self, init: bool, f: impl FnOnce(&Context, Rc<S>)) {
+        let Some(cx) = self.borrow().cx.as_ref().and_then(WeakContext::upgrade) else {
+            return;
+        };
+
+        let state = if init { Some(cx.get::<S>()) } else { cx.peek::<S>() };
+        if let Some(state) = state {
+            f(&cx, state);
+        }
     }
 
     pub(crate) fn notify(&self, state: Rc<S>) {
-        for (_, subscriber) in &self.borrow().0 {
-            subscriber.call(Rc::clone(&state));
+        // Not borrowed while calling, so subscribers may (un)subscribe.
+        let subscribers = self
+            .borrow()
+            .slab
+            .iter()
+            .map(|(key, subscriber)| (key, Rc::clone(subscriber)))
+            .collect::<Vec<_>>();
+
+        for (key, subscriber) in subscribers {
+            // Skip subscribers removed during this notification. Keys may be reused, so check
+            // it's the same subscriber.
+            let subscribed = self
+                .borrow()
+                .slab
+                .get(key)
+                .is_some_and(|current| Rc::ptr_eq(current, &subscriber));
+
+            if subscribed {
+                subscriber.call(Rc::clone(&state));
+            }
         }
     }
 }
@@ -47,7 +125,11 @@ impl<S> PartialEq for Subscribers<S> {
 
 impl<S> Default for Subscribers<S> {
     fn default() -> Self {
-        Self(Default::default())
+        Self {
+            slab: Default::default(),
+            active: 0,
+            cx: None,
+        }
     }
 }
 
@@ -55,6 +137,7 @@ impl<S> Default for Subscribers<S> {
 pub struct SubscriberId<S: Store> {
     subscribers_ref: Mrc<Subscribers<S>>,
     pub(crate) key: usize,
+    active: bool,
     pub(crate) _store_type: PhantomData<S>,
 }
 
@@ -82,7 +165,7 @@ impl<S: Store> SubscriberId<S> {
 
 impl<S: Store> Drop for SubscriberId<S> {
     fn drop(&mut self) {
-        self.subscribers_ref.unsubscribe(self.key)
+        self.subscribers_ref.unsubscribe(self.key, self.active)
     }
 }
 
@@ -128,11 +211,11 @@ mod tests {
         let cx = Context::new();
         let entry = cx.get_or_init_default::<Mrc<Subscribers<TestState>>>();
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
 
         let _id = Dispatch::new(&cx).subscribe(|_: Rc<TestState>| ());
 
-        assert!(!entry.store.borrow().borrow().0.is_empty());
+        assert!(!entry.store.borrow().borrow().slab.is_empty());
     }
 
     #[test]
@@ -140,15 +223,15 @@ mod tests {
         let cx = Context::new();
         let entry = cx.get_or_init_default::<Mrc<Subscribers<TestState>>>();
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
 
         let id = Dispatch::new(&cx).subscribe(|_: Rc<TestState>| ());
 
-        assert!(!entry.store.borrow().borrow().0.is_empty());
+        assert!(!entry.store.borrow().borrow().slab.is_empty());
 
         drop(id);
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
     }
 
     #[test]
@@ -156,15 +239,15 @@ mod tests {
         let cx = Context::new();
         let entry = cx.get_or_init_default::<Mrc<Subscribers<TestState>>>();
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
 
         let id = Dispatch::<TestState>::new(&cx).subscribe(|_| {});
 
-        assert!(!entry.store.borrow().borrow().0.is_empty());
+        assert!(!entry.store.borrow().borrow().slab.is_empty());
 
         drop(id);
 
-        assert!(entry.store.borrow().borrow().0.is_empty());
+        assert!(entry.store.borrow().borrow().slab.is_empty());
     }
 
     #[test]
@@ -212,4 +295,149 @@ mod tests {
 
         assert_eq!(dispatch.get().0, 1)
     }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Lifecycle;
+    impl Store for Lifecycle {
+        fn new(_cx: &Context) -> Self {
+            Self
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+
+        fn on_active(&self, cx: &Context) {
+            cx.reduce_mut(|state: &mut TestState| state.0 += 1);
+        }
+
+        fn on_inactive(&self, cx: &Context) {
+            cx.reduce_mut(|state: &mut TestState| state.0 += 10);
+        }
+    }
+
+    struct LifecycleListener;
+    impl crate::Listener for LifecycleListener {
+        type Store = Lifecycle;
+
+        fn on_change(&self, _cx: &Context, _state: Rc<Self::Store>) {}
+    }
+
+    #[test]
+    fn on_active_is_called_for_first_subscriber() {
+        let cx = Context::new();
+
+        let _id1 = cx.subscribe_silent::<Lifecycle, _>(|_| {});
+        assert_eq!(cx.get::<TestState>().0, 1);
+
+        let _id2 = cx.subscribe::<Lifecycle, _>(|_| {});
+        assert_eq!(cx.get::<TestState>().0, 1);
+    }
+
+    #[test]
+    fn on_inactive_is_called_for_last_subscriber() {
+        let cx = Context::new();
+        let id1 = cx.subscribe_silent::<Lifecycle, _>(|_| {});
+        let id2 = cx.subscribe_silent::<Lifecycle, _>(|_| {});
+
+        drop(id1);
+        assert_eq!(cx.get::<TestState>().0, 1);
+
+        drop(id2);
+        assert_eq!(cx.get::<TestState>().0, 11);
+
+        // Active again.
+        let _id = cx.subscribe_silent::<Lifecycle, _>(|_| {});
+        assert_eq!(cx.get::<TestState>().0, 12);
+    }
+
+    #[test]
+    fn listeners_do_not_activate_store() {
+        let cx = Context::new();
+
+        cx.init_listener(|| LifecycleListener);
+        assert_eq!(cx.get::<TestState>().0, 0);
+
+        let id = cx.subscribe_silent::<Lifecycle, _>(|_| {});
+        drop(id);
+        assert_eq!(cx.get::<TestState>().0, 11);
+    }
+
+    #[test]
+    fn on_inactive_is_not_called_for_removed_store() {
+        let cx = Context::new();
+        let id = cx.subscribe_silent::<Lifecycle, _>(|_| {});
+
+        cx.remove::<Lifecycle>();
+        drop(id);
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+    }
+
+    #[test]
+    fn can_subscribe_while_notifying() {
+        let cx = Context::new();
+        let calls = Mrc::new(Vec::new());
+        let ids = Mrc::new(Vec::new());
+        let _id = {
+            let (cx, calls, ids) = (cx.clone(), calls.clone(), ids.clone());
+            cx.clone().subscribe_silent(move |_: Rc<TestState>| {
+                let calls = calls.clone();
+                let id = cx.subscribe_silent(move |state: Rc<TestState>| {
+                    calls.borrow_mut().push(state.0);
+                });
+                ids.borrow_mut().push(id);
+            })
+        };
+
+        cx.set(TestState(1));
+        // Added during the notification, so not notified of it.
+        assert!(calls.borrow().is_empty());
+
+        cx.set(TestState(2));
+        assert_eq!(*calls.borrow(), vec![2]);
+    }
+
+    #[test]
+    fn can_unsubscribe_while_notifying() {
+        let cx = Context::new();
+        let calls = Mrc::new(0);
+        let second = Mrc::new(None);
+        let _first = {
+            let second = second.clone();
+            cx.subscribe_silent(move |_: Rc<TestState>| {
+                // Unsubscribes the next subscriber before its turn.
+                second.borrow_mut().take();
+            })
+        };
+        *second.borrow_mut() = {
+            let calls = calls.clone();
+            Some(cx.subscribe_silent(move |_: Rc<TestState>| *calls.borrow_mut() += 1))
+        };
+
+        cx.set(TestState(1));
+
+        assert_eq!(*calls.borrow(), 0);
+        assert_eq!(cx.subscriber_count::<TestState>(), 1);
+    }
+
+    #[test]
+    fn subscriber_can_drop_itself_while_notifying() {
+        let cx = Context::new();
+        let calls = Mrc::new(0);
+        let id = Mrc::new(None);
+        *id.borrow_mut() = {
+            let (id, calls) = (id.clone(), calls.clone());
+            Some(cx.subscribe_silent(move |_: Rc<TestState>| {
+                *calls.borrow_mut() += 1;
+                id.borrow_mut().take();
+            }))
+        };
+
+        cx.set(TestState(1));
+        cx.set(TestState(2));
+
+        assert_eq!(*calls.borrow(), 1);
+        assert_eq!(cx.subscriber_count::<TestState>(), 0);
+    }
 }
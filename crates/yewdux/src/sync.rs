@@ -0,0 +1,248 @@
+//! Building state on other threads.
+//!
+//! [Context] is single threaded, so it can't be shared between the threads of a multi-threaded
+//! server. A [SyncContext] can: it holds stores behind an [Arc] and a lock, so they can be built or
+//! pre-computed on any thread. Once ready, [SyncContext::to_context] makes a [Context] with the
+//! same stores, on the thread that renders.
+//!
+//! ```
+//! use yewdux::{prelude::*, sync::SyncContext};
+//!
+//! #[derive(Default, Clone, PartialEq, Store)]
+//! struct Config {
+//!     title: String,
+//! }
+//!
+//! let shared = SyncContext::new();
+//! std::thread::spawn({
+//!     let shared = shared.clone();
+//!     move || shared.set(Config { title: "Yewdux".to_string() })
+//! })
+//! .join()
+//! .unwrap();
+//!
+//! let cx = shared.to_context();
+//! assert_eq!(cx.get::<Config>().title, "Yewdux");
+//! ```
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use crate::{store::Store, Context};
+
+/// Copies a store into a [Context].
+type Apply = fn(&Context, &(dyn Any + Send + Sync));
+
+struct Entry {
+    value: Arc<dyn Any + Send + Sync>,
+    apply: Apply,
+}
+
+/// A thread-safe collection of stores. See the [module docs](self).
+///
+/// Cloning it shares the same stores.
+#[derive(Clone, Default)]
+pub struct SyncContext {
+    stores: Arc<RwLock<HashMap<TypeId, Entry>>>,
+}
+
+impl std::fmt::Debug for SyncContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncContext").finish()
+    }
+}
+
+impl SyncContext {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set state to given value.
+    pub fn set<S>(&self, value: S)
+    where
+        S: Store + Clone + Send + Sync,
+    {
+        let entry = Entry {
+            value: Arc::new(value),
+            apply: apply::<S>,
+        };
+        let old = self
+            .stores
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(TypeId::of::<S>(), entry);
+        // Dropped outside the lock, as dropping a store may use this context.
+        drop(old);
+    }
+
+    /// Get current state. A store that wasn't set yet is initialized with [Store::new], in a new
+    /// [Context] that is dropped right after. So `new` doesn't see the other stores set here, and
+    /// listeners or derived stores it sets up are dropped too. Use [Self::set] first for stores
+    /// that depend on them.
+    pub fn get<S>(&self) -> Arc<S>
+    where
+        S: Store + Clone + Send + Sync,
+    {
+        if let Some(value) = self.peek::<S>() {
+            return value;
+        }
+
+        let value = S::clone(&Context::new().get::<S>());
+        let mut stores = self.stores.write().unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have set it meanwhile.
+        let entry = stores.entry(TypeId::of::<S>()).or_insert_with(|| Entry {
+            value: Arc::new(value),
+            apply: apply::<S>,
+        });
+
+        downcast(&entry.value)
+    }
+
+    /// Get current state, if it was set.
+    pub fn peek<S>(&self) -> Option<Arc<S>>
+    where
+        S: Store + Send + Sync,
+    {
+        self.stores
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&TypeId::of::<S>())
+            .map(|entry| downcast(&entry.value))
+    }
+
+    /// Mutate state with given function.
+    ///
+    /// The context is locked while `f` runs, so `f` must not use it.
+    pub fn reduce_mut<S, F>(&self, f: F)
+    where
+        S: Store + Clone + Send + Sync,
+        F: FnOnce(&mut S),
+    {
+        // Initialized outside the lock, as `Store::new` may take a while.
+        self.get::<S>();
+
+        let mut stores = self.stores.write().unwrap_or_else(PoisonError::into_inner);
+        let entry = stores
+            .get_mut(&TypeId::of::<S>())
+            .expect("store was initialized");
+        // Changes are only kept if `f` completes.
+        let mut value = S::clone(&downcast(&entry.value));
+        f(&mut value);
+        let old = std::mem::replace(&mut entry.value, Arc::new(value));
+
+        // Dropped outside the lock, like in `set`.
+        drop(stores);
+        drop(old);
+    }
+
+    /// Create a [Context] with a copy of every store set here. Stores are initialized with their
+    /// copy instead of [Store::new].
+    pub fn to_context(&self) -> Context {
+        // Collected first, so the lock isn't held while stores are initialized.
+        let stores = self
+            .stores
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(|entry| (entry.apply, Arc::clone(&entry.value)))
+            .collect::<Vec<_>>();
+
+        let cx = Context::new();
+        for (apply, value) in stores {
+            apply(&cx, &*value);
+        }
+
+        cx
+    }
+}
+
+fn downcast<S: Send + Sync + 'static>(value: &Arc<dyn Any + Send + Sync>) -> Arc<S> {
+    Arc::clone(value)
+        .downcast::<S>()
+        .expect("store type mismatch")
+}
+
+fn apply<S: Store + Clone>(cx: &Context, value: &(dyn Any + Send + Sync)) {
+    let value = value.downcast_ref::<S>().expect("store type mismatch");
+    let mut value = Some(value.clone());
+    cx.init(|_| value.take().expect("value already taken"));
+    // Already initialized by another store.
+    if let Some(value) = value {
+        cx.set(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(1)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[test]
+    fn stores_are_initialized_with_new() {
+        let cx = SyncContext::new();
+        assert!(cx.peek::<TestState>().is_none());
+
+        assert_eq!(*cx.get::<TestState>(), TestState(1));
+        assert!(cx.peek::<TestState>().is_some());
+    }
+
+    #[test]
+    fn can_reduce_from_many_threads() {
+        let cx = SyncContext::new();
+        let threads = (0..8)
+            .map(|_| {
+                let cx = cx.clone();
+                std::thread::spawn(move || cx.reduce_mut(|state: &mut TestState| state.0 += 1))
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(*cx.get::<TestState>(), TestState(9));
+    }
+
+    #[test]
+    fn context_has_a_copy_of_stores() {
+        let shared = SyncContext::new();
+        shared.set(TestState(5));
+
+        let cx = shared.to_context();
+        shared.set(TestState(6));
+
+        assert_eq!(*cx.get::<TestState>(), TestState(5));
+    }
+
+    #[test]
+    fn context_stores_are_not_created_with_new() {
+        #[derive(Clone)]
+        struct Set(u32);
+        impl Store for Set {
+            fn new(_cx: &Context) -> Self {
+                panic!("created with new")
+            }
+
+            fn should_notify(&self, _other: &Self) -> bool {
+                true
+            }
+        }
+
+        let shared = SyncContext::new();
+        shared.set(Set(2));
+
+        assert_eq!(shared.to_context().get::<Set>().0, 2);
+    }
+}
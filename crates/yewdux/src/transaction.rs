@@ -0,0 +1,233 @@
+//! Atomic updates across multiple stores.
+//!
+//! See [Context::transaction](crate::Context::transaction).
+use std::{any::TypeId, cell::RefCell, rc::Rc};
+
+use crate::{
+    introspection::Registry,
+    store::{Reducer, Store},
+    Context,
+};
+
+struct Snapshot {
+    type_id: TypeId,
+    restore: Box<dyn FnOnce(&Context)>,
+}
+
+/// Handle for changing state inside of [Context::transaction](crate::Context::transaction).
+///
+/// The state of every store is saved the first time it is changed through this handle, so it may be
+/// restored if the transaction fails. Changes made through other means (like a [Dispatch](crate::Dispatch))
+/// are not rolled back.
+///
+/// Changes made in a transaction go through [middleware](crate::middleware) and are recorded as
+/// usual, even if it fails. Restoring saved state doesn't: middleware, the
+/// [ActionLog](crate::action_log::ActionLog) and devtools don't see it. It only counts as a change
+/// in the [revision](Context::revision) of the context.
+pub struct Transaction<'a> {
+    cx: &'a Context,
+    snapshots: RefCell<Vec<Snapshot>>,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(cx: &'a Context) -> Self {
+        Self {
+            cx,
+            snapshots: Default::default(),
+        }
+    }
+
+    /// Get the context this transaction is running in.
+    pub fn context(&self) -> &Context {
+        self.cx
+    }
+
+    /// Get current state.
+    pub fn get<S: Store>(&self) -> Rc<S> {
+        self.cx.get::<S>()
+    }
+
+    pub fn reduce<S: Store, R: Reducer<S>>(&self, r: R) {
+        self.snapshot::<S>();
+        self.cx.reduce(r);
+    }
+
+    pub fn reduce_mut<S: Store + Clone, F: FnOnce(&mut S)>(&self, f: F) {
+        self.snapshot::<S>();
+        self.cx.reduce_mut(f);
+    }
+
+    /// Set state to given value.
+    pub fn set<S: Store>(&self, value: S) {
+        self.snapshot::<S>();
+        self.cx.set(value);
+    }
+
+    /// Save the current state of `S`, if it hasn't been saved already.
+    fn snapshot<S: Store>(&self) {
+        let type_id = TypeId::of::<S>();
+        if self.snapshots.borrow().iter().any(|x| x.type_id == type_id) {
+            return;
+        }
+
+        let old = self.cx.get::<S>();
+        // A notification that was pending before this transaction started belongs to an outer
+        // batch, so it must survive a rollback.
+        let was_pending = self.cx.is_pending::<S>();
+        let restore = Box::new(move |cx: &Context| {
+            let cx = cx.owner::<S>();
+            *cx.get_or_init_default::<S>().store.borrow_mut() = old;
+            cx.internal::<Registry>()
+                .with_mut(|registry| registry.changed::<S>());
+            if !was_pending {
+                cx.cancel_notify::<S>();
+            }
+        });
+
+        self.snapshots
+            .borrow_mut()
+            .push(Snapshot { type_id, restore });
+    }
+
+    /// Restore every changed store to its saved state, without notifying subscribers.
+    pub(crate) fn rollback(self) {
+        for snapshot in self.snapshots.into_inner().into_iter().rev() {
+            (snapshot.restore)(self.cx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState(u32);
+    impl Store for TestState {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct TestState2(u32);
+    impl Store for TestState2 {
+        fn new(_cx: &Context) -> Self {
+            Self(0)
+        }
+
+        fn should_notify(&self, other: &Self) -> bool {
+            self != other
+        }
+    }
+
+    #[test]
+    fn commits_on_ok() {
+        let cx = Context::new();
+        let result: Result<(), ()> = cx.transaction(|tx| {
+            tx.set(TestState(1));
+            tx.reduce_mut(|state: &mut TestState2| state.0 = 2);
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(cx.get::<TestState>().0, 1);
+        assert_eq!(cx.get::<TestState2>().0, 2);
+    }
+
+    #[test]
+    fn rolls_back_on_err() {
+        let cx = Context::new();
+        cx.set(TestState(1));
+
+        let result: Result<(), ()> = cx.transaction(|tx| {
+            tx.set(TestState(2));
+            tx.reduce(|state: Rc<TestState>| TestState(state.0 + 1).into());
+            tx.reduce_mut(|state: &mut TestState2| state.0 = 2);
+            Err(())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(cx.get::<TestState>().0, 1);
+        assert_eq!(cx.get::<TestState2>().0, 0);
+    }
+
+    #[test]
+    fn rollback_is_a_new_revision() {
+        let cx = Context::new();
+        cx.set(TestState(1));
+        let revision = cx.revision();
+
+        let _ = cx.transaction(|tx| {
+            tx.set(TestState(2));
+            Err::<(), _>(())
+        });
+
+        // One for the change, and one for restoring it.
+        assert_eq!(cx.revision(), revision + 2);
+        let info = cx.store_info::<TestState>().unwrap();
+        assert_eq!(info.last_changed, Some(cx.revision()));
+    }
+
+    #[test]
+    fn rollback_does_not_notify() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        let _ = cx.transaction(|tx| {
+            tx.set(TestState(1));
+            Err::<(), _>(())
+        });
+
+        assert_eq!(calls.get(), 0);
+    }
+
+    #[test]
+    fn commit_notifies_once() {
+        let cx = Context::new();
+        let calls = Rc::new(Cell::new(0));
+        let _id = {
+            let calls = calls.clone();
+            cx.subscribe_silent::<TestState, _>(move |_| calls.set(calls.get() + 1))
+        };
+
+        let _ = cx.transaction(|tx| {
+            tx.set(TestState(1));
+            tx.set(TestState(2));
+            Ok::<_, ()>(())
+        });
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn rollback_keeps_changes_from_outer_batch() {
+        let cx = Context::new();
+        let seen = Rc::new(Cell::new(0));
+        let _id = {
+            let seen = seen.clone();
+            cx.subscribe_silent::<TestState, _>(move |state: Rc<TestState>| seen.set(state.0))
+        };
+
+        cx.batch(|cx| {
+            cx.set(TestState(1));
+            let _ = cx.transaction(|tx| {
+                tx.set(TestState(2));
+                Err::<(), _>(())
+            });
+        });
+
+        assert_eq!(cx.get::<TestState>().0, 1);
+        assert_eq!(seen.get(), 1);
+    }
+}
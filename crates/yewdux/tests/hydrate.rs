@@ -0,0 +1,37 @@
+use std::{cell::Cell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use yewdux::{hydrate, listener::Listener, Context, Store};
+
+thread_local! {
+    static CHANGES: Cell<u32> = const { Cell::new(0) };
+}
+
+struct Recorder;
+impl Listener for Recorder {
+    type Store = State;
+
+    fn on_change(&self, _cx: &Context, _state: Rc<Self::Store>) {
+        CHANGES.with(|changes| changes.set(changes.get() + 1));
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Serialize, Deserialize, Store)]
+#[store(hydrate, storage = "local", listener(Recorder))]
+struct State(u32);
+
+#[test]
+fn hydrated_store_registers_listener() {
+    let server = Context::new();
+    server.set(State(1));
+    let payload = hydrate::serialize(&server).unwrap();
+
+    let client = Context::new();
+    hydrate::load(&client, &payload).unwrap();
+    assert_eq!(client.get::<State>().0, 1);
+
+    CHANGES.with(|changes| changes.set(0));
+    client.set(State(2));
+
+    assert_eq!(CHANGES.with(Cell::get), 1);
+}